@@ -0,0 +1,83 @@
+//! Bech32 addresses
+//!
+//! Addresses are derived from public keys (see [`PubKey::address`]) and
+//! rendered with a chain-specific human-readable prefix, following the
+//! Cosmos SDK conventions: `<prefix>` for accounts, `<prefix>valoper` for
+//! validator operators and `<prefix>valcons` for validator consensus nodes.
+
+use crate::crypto::PubKey;
+use crate::error::{Error, ErrorKind};
+use abscissa_core::format_err;
+use bech32::{FromBase32, ToBase32, Variant};
+use std::fmt;
+
+/// Bech32 prefix of the Cosmos Hub, used when none is configured
+pub const DEFAULT_BECH32_PREFIX: &str = "cosmos";
+
+/// Suffix appended to the chain prefix for validator operator addresses
+const VALIDATOR_OPERATOR_SUFFIX: &str = "valoper";
+
+/// Suffix appended to the chain prefix for validator consensus addresses
+const VALIDATOR_CONSENSUS_SUFFIX: &str = "valcons";
+
+/// Kinds of addresses, each with their own human-readable part
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum AddressKind {
+    /// Account address
+    #[default]
+    Acc,
+    /// Validator operator address
+    Val,
+    /// Validator consensus address
+    Cons,
+}
+
+impl AddressKind {
+    /// Human-readable part of addresses of this kind on a chain using `prefix`
+    pub fn hrp(self, prefix: &str) -> String {
+        match self {
+            AddressKind::Acc => prefix.to_owned(),
+            AddressKind::Val => format!("{}{}", prefix, VALIDATOR_OPERATOR_SUFFIX),
+            AddressKind::Cons => format!("{}{}", prefix, VALIDATOR_CONSENSUS_SUFFIX),
+        }
+    }
+}
+
+impl fmt::Display for AddressKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AddressKind::Acc => "acc",
+            AddressKind::Val => "val",
+            AddressKind::Cons => "cons",
+        })
+    }
+}
+
+/// Bech32-encode raw address bytes with the given human-readable part
+pub fn encode(hrp: &str, bytes: &[u8]) -> Result<String, Error> {
+    bech32::encode(hrp, bytes.to_base32(), Variant::Bech32)
+        .map_err(|e| format_err!(ErrorKind::Address, "invalid bech32 prefix {}: {}", hrp, e).into())
+}
+
+/// Decode a bech32 address into its human-readable part and raw bytes
+pub fn decode(address: &str) -> Result<(String, Vec<u8>), Error> {
+    let (hrp, data, variant) = bech32::decode(address)
+        .map_err(|e| format_err!(ErrorKind::Address, "invalid address {}: {}", address, e))?;
+
+    if variant != Variant::Bech32 {
+        return Err(format_err!(
+            ErrorKind::Address,
+            "invalid address {}: not bech32",
+            address
+        )
+        .into());
+    }
+
+    let bytes = Vec::<u8>::from_base32(&data).map_err(|e| ErrorKind::Address.context(e))?;
+    Ok((hrp, bytes))
+}
+
+/// Address of `pub_key`, of the given kind, on a chain using `prefix`
+pub fn pub_key_address(pub_key: &PubKey, kind: AddressKind, prefix: &str) -> Result<String, Error> {
+    encode(&kind.hrp(prefix), &pub_key.address())
+}
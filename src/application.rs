@@ -73,6 +73,11 @@ impl Application for AmonApp {
         // Configure components
         let mut components = self.state.components_mut();
         components.after_config(&config)?;
+
+        // apply the log level of the configuration
+        if let Some(tracing) = components.get_downcast_mut::<trace::Tracing>() {
+            tracing.reload_filter(config.global.log_level.to_string());
+        }
         self.config.set_once(config);
         Ok(())
     }
@@ -9,23 +9,49 @@
 //!
 //! See the `impl Configurable` below for how to specify the path to the
 //! application's configuration file.
+//!
+//! Amon keeps its configuration and file-based keyrings in a home directory:
+//! the one given with `--home`, or the `AMON_HOME` environment variable, or
+//! `~/.amon`. The configuration is read from `config.toml` in that directory
+//! unless another file is given with `-c`/`--config`; when there is none,
+//! the default configuration is used.
 
 mod keys;
 mod start;
 
 use self::keys::KeysCmd;
 use self::start::StartCmd;
-use crate::config::AmonConfig;
-use abscissa_core::{config::Override, Command, Configurable, FrameworkError, Runnable};
-use std::path::PathBuf;
-use tracing::{error, info};
+use crate::config::{AmonConfig, LogLevel};
+use crate::error::ErrorKind;
+use crate::keyring::KeyringBackend;
+use abscissa_core::{
+    config::Override, format_err, Command, Configurable, FrameworkError, FrameworkErrorKind,
+    Runnable,
+};
+use std::{env, path::PathBuf};
+use tracing::debug;
+
+/// Amon home directory, relative to the user's home directory
+pub const DEFAULT_HOME_DIR: &str = ".amon";
 
-/// Amon Configuration Filename
-pub const DEFAULT_CONFIG_PATH: &str = ".amon/config.toml";
+/// Amon Configuration Filename, relative to the Amon home directory
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Environment variable overriding the default home directory
+pub const HOME_ENV: &str = "AMON_HOME";
+
+/// Default home directory, holding the configuration and file-based keyrings:
+/// `$AMON_HOME` if set, `~/.amon` otherwise
+pub fn default_home_dir() -> Option<PathBuf> {
+    match env::var_os(HOME_ENV) {
+        Some(home) if !home.is_empty() => Some(PathBuf::from(home)),
+        _ => dirs_next::home_dir().map(|home| home.join(DEFAULT_HOME_DIR)),
+    }
+}
 
 /// Default configuration file path
 pub fn default_config_file() -> Option<PathBuf> {
-    dirs_next::home_dir().map(|home| home.join(DEFAULT_CONFIG_PATH))
+    default_home_dir().map(|home| home.join(CONFIG_FILE_NAME))
 }
 
 /// Amon Subcommands
@@ -50,9 +76,36 @@ pub struct EntryPoint {
     #[arg(short, long)]
     pub verbose: bool,
 
-    /// Use the specified config file
+    /// Use the specified config file instead of the one in the home directory
     #[arg(short, long)]
-    pub config: Option<String>,
+    pub config: Option<PathBuf>,
+
+    /// Directory holding the configuration and keyrings; defaults to $AMON_HOME or ~/.amon
+    #[arg(long, global = true)]
+    pub home: Option<PathBuf>,
+
+    /// Select keyring's backend
+    #[arg(long, global = true, value_enum)]
+    pub keyring_backend: Option<KeyringBackend>,
+
+    /// The client keyring directory; if omitted, the default home directory will be used
+    #[arg(long, global = true)]
+    pub keyring_dir: Option<PathBuf>,
+
+    /// Chain to operate on, among the `[[chains]]` of the configuration; defaults to the first one
+    #[arg(long, global = true)]
+    pub chain: Option<String>,
+
+    /// Human-readable prefix of account addresses, e.g. `cosmos`
+    #[arg(long, global = true)]
+    pub bech32_prefix: Option<String>,
+}
+
+impl EntryPoint {
+    /// Home directory selected on the command line or by default
+    pub fn home_dir(&self) -> Option<PathBuf> {
+        self.home.clone().or_else(default_home_dir)
+    }
 }
 
 impl Runnable for EntryPoint {
@@ -63,31 +116,20 @@ impl Runnable for EntryPoint {
 
 /// This trait allows you to define how application configuration is loaded.
 impl Configurable<AmonConfig> for EntryPoint {
-    /// Location of the configuration file
+    /// Location of the configuration file: the one given with `--config`,
+    /// or `config.toml` in the home directory if it exists
     fn config_path(&self) -> Option<PathBuf> {
-        let path = default_config_file();
-
-        match path {
-            Some(path) if path.exists() => {
-                info!("using default configuration from '{}'", path.display());
-                Some(path)
-            }
-            Some(path) => {
-                // No file exists at the config path
-                error!("could not find configuration file at '{}'", path.display());
-                error!("for an example, please see https://hermes.informal.systems/config.html#example-configuration-file");
-                None
-            }
-            None => {
-                // The path to the default config file could not be found
-                error!("could not find default configuration file");
-                error!(
-                    "please create one at '~/{}' or specify it with the '-c'/'--config' flag",
-                    DEFAULT_CONFIG_PATH
-                );
-                error!("for an example, please see https://hermes.informal.systems/config.html#example-configuration-file");
-                None
-            }
+        if let Some(path) = &self.config {
+            return Some(path.clone());
+        }
+
+        let path = self.home_dir()?.join(CONFIG_FILE_NAME);
+        if path.exists() {
+            debug!("using configuration from '{}'", path.display());
+            Some(path)
+        } else {
+            debug!("no configuration at '{}', using defaults", path.display());
+            None
         }
     }
 
@@ -96,7 +138,42 @@ impl Configurable<AmonConfig> for EntryPoint {
     ///
     /// This can be safely deleted if you don't want to override config
     /// settings from command-line options.
-    fn process_config(&self, config: AmonConfig) -> Result<AmonConfig, FrameworkError> {
+    fn process_config(&self, mut config: AmonConfig) -> Result<AmonConfig, FrameworkError> {
+        if let Some(backend) = self.keyring_backend {
+            config.global.keyring_backend = backend;
+        }
+
+        if let Some(dir) = &self.keyring_dir {
+            config.global.keyring_dir = Some(dir.clone());
+        }
+
+        if config.global.keyring_dir.is_none() {
+            config.global.keyring_dir = self.home_dir();
+        }
+
+        if self.verbose {
+            config.global.log_level = LogLevel::Debug;
+        }
+
+        config.chain = match &self.chain {
+            Some(id) => config.find_chain(id).cloned().ok_or_else(|| {
+                FrameworkErrorKind::ConfigError.context(format_err!(
+                    ErrorKind::Config,
+                    "unknown chain {}",
+                    id
+                ))
+            })?,
+            None => config.chains.first().cloned().unwrap_or_default(),
+        };
+
+        if let Some(prefix) = &self.bech32_prefix {
+            config.chain.bech32_prefix = prefix.clone();
+        }
+
+        config
+            .validate()
+            .map_err(|e| FrameworkErrorKind::ConfigError.context(e))?;
+
         match &self.cmd {
             AmonCmd::Start(cmd) => cmd.override_config(config),
             AmonCmd::Keys(cmd) => cmd.override_config(config),
@@ -4,8 +4,18 @@
 /// accessors along with logging macros. Customize as you see fit.
 use crate::prelude::*;
 
+use super::input;
+use super::output::{mk_acc_key_output, print_key_output, OutputFormat};
+use crate::address::{self, AddressKind};
 use crate::config::AmonConfig;
+use crate::crypto::{LegacyAminoPubKey, PubKey, SigningAlgo};
+use crate::error::{Error, ErrorKind};
+use crate::hd::{self, HdPath};
+use crate::keyring::{KeyRecord, Keyring, MemoryKeyring};
+use crate::ledger::CosmosApp;
 use abscissa_core::{config, Command, FrameworkError, Runnable};
+use bip39::Mnemonic;
+use std::process;
 
 /// add subcommand - Add an encrypted private key (either newly generated or recovered), encrypt it, and save to <name> file
 ///
@@ -37,18 +47,19 @@ pub struct AddCmd {
     /// Account number for HD derivation (less than equal 2147483647)
     #[arg(long, default_value_t = 0)]
     pub account: u32,
-    /// Key signing algorithm to generate keys for
-    #[arg(long, default_value_t = String::from("secp256k1"))]
-    pub algo: String, // todo
-    /// coin type number for HD derivation
-    #[arg(short, long, default_value_t = 118)]
-    pub coin_type: u32, // todo
+    /// Key signing algorithm to generate keys for; eth_secp256k1 keys default to coin type 60
+    #[arg(long, value_enum, default_value_t = SigningAlgo::Secp256k1)]
+    pub algo: SigningAlgo,
+    /// coin type number for HD derivation, defaults to the `coin_type` of the selected chain, or
+    /// else to the usual one for --algo
+    #[arg(short, long)]
+    pub coin_type: Option<u32>,
     /// Perform action, but don't add key to local keystore
     #[arg(long, default_value_t = false)]
     pub dry_run: bool,
     /// Manual HD Path derivation (overrides BIP44 config)
     #[arg(long)]
-    pub hd_path: Option<String>,
+    pub hd_path: Option<HdPath>,
     /// Address index number for HD derivation (less than equal 2147483647)
     #[arg(short, long, default_value_t = 0)]
     pub index: u32,
@@ -59,11 +70,11 @@ pub struct AddCmd {
     #[arg(long, default_value_t = false)]
     pub ledger: bool,
     /// List of key names stored in keyring to construct a public legacy multisig key
-    #[arg(long)]
-    pub multisig: Option<String>,
+    #[arg(long, value_delimiter = ',')]
+    pub multisig: Vec<String>,
     /// K out of N required signatures. For use in conjunction with --multisig
     #[arg(long, default_value_t = 1)]
-    pub multisig_threshold: i32,
+    pub multisig_threshold: u32,
     /// Don't print out seed phrase (if others are watching the terminal)
     #[arg(long, default_value_t = false)]
     pub no_backup: bool,
@@ -76,6 +87,9 @@ pub struct AddCmd {
     /// Provide seed phrase to recover existing key instead of creating
     #[arg(long, default_value_t = false)]
     pub recover: bool,
+    /// Output format, defaults to the `output` setting of the configuration
+    #[arg(short, long, value_enum)]
+    pub output: Option<OutputFormat>,
 }
 
 impl Runnable for AddCmd {
@@ -90,196 +104,167 @@ impl Runnable for AddCmd {
     */
     /// Start the application.
     fn run(&self) {
-        println!("add subcommand!");
-        //        var err error
-
-        // name := args[0]
-        let name = self.name.clone();
-        // interactive, _ := cmd.Flags().GetBool(flagInteractive)
-        let interactive = self.interactive;
-        // noBackup, _ := cmd.Flags().GetBool(flagNoBackup)
-        let no_backup = self.no_backup;
-        // showMnemonic := !noBackup
-        let show_mnemonic = !no_backup;
-        // kb := ctx.Keyring
-        // outputFormat := ctx.OutputFormat
-
-        // keyringAlgos, _ := kb.SupportedAlgorithms()
-        // algoStr, _ := cmd.Flags().GetString(flags.FlagKeyType)
-        // algo, err := keyring.NewSigningAlgoFromString(algoStr, keyringAlgos)
-        // if err != nil {
-        // 	return err
-        // }
-
-        // if dryRun, _ := cmd.Flags().GetBool(flags.FlagDryRun); dryRun {
-        // 	// use in memory keybase
-        // 	kb = keyring.NewInMemory(ctx.Codec)
-        // } else {
-        // 	_, err = kb.Key(name)
-        // 	if err == nil {
-        // 		// account exists, ask for user confirmation
-        // 		response, err2 := input.GetConfirmation(fmt.Sprintf("override the existing name %s", name), inBuf, cmd.ErrOrStderr())
-        // 		if err2 != nil {
-        // 			return err2
-        // 		}
-
-        // 		if !response {
-        // 			return errors.New("aborted")
-        // 		}
-
-        // 		err2 = kb.Delete(name)
-        // 		if err2 != nil {
-        // 			return err2
-        // 		}
-        // 	}
-
-        // 	multisigKeys, _ := cmd.Flags().GetStringSlice(flagMultisig)
-        // 	if len(multisigKeys) != 0 {
-        // 		pks := make([]cryptotypes.PubKey, len(multisigKeys))
-        // 		multisigThreshold, _ := cmd.Flags().GetInt(flagMultiSigThreshold)
-        // 		if err := validateMultisigThreshold(multisigThreshold, len(multisigKeys)); err != nil {
-        // 			return err
-        // 		}
-
-        // 		for i, keyname := range multisigKeys {
-        // 			k, err := kb.Key(keyname)
-        // 			if err != nil {
-        // 				return err
-        // 			}
-
-        // 			key, err := k.GetPubKey()
-        // 			if err != nil {
-        // 				return err
-        // 			}
-        // 			pks[i] = key
-        // 		}
-
-        // 		if noSort, _ := cmd.Flags().GetBool(flagNoSort); !noSort {
-        // 			sort.Slice(pks, func(i, j int) bool {
-        // 				return bytes.Compare(pks[i].Address(), pks[j].Address()) < 0
-        // 			})
-        // 		}
-
-        // 		pk := multisig.NewLegacyAminoPubKey(multisigThreshold, pks)
-        // 		k, err := kb.SaveMultisig(name, pk)
-        // 		if err != nil {
-        // 			return err
-        // 		}
-
-        // 		return printCreate(cmd, k, false, "", outputFormat)
-        // 	}
-        // }
-
-        // pubKey, _ := cmd.Flags().GetString(FlagPublicKey)
-        // if pubKey != "" {
-        // 	var pk cryptotypes.PubKey
-        // 	if err = ctx.Codec.UnmarshalInterfaceJSON([]byte(pubKey), &pk); err != nil {
-        // 		return err
-        // 	}
-
-        // 	k, err := kb.SaveOfflineKey(name, pk)
-        // 	if err != nil {
-        // 		return err
-        // 	}
-
-        // 	return printCreate(cmd, k, false, "", outputFormat)
-        // }
-
-        // coinType, _ := cmd.Flags().GetUint32(flagCoinType)
-        // account, _ := cmd.Flags().GetUint32(flagAccount)
-        // index, _ := cmd.Flags().GetUint32(flagIndex)
-        // hdPath, _ := cmd.Flags().GetString(flagHDPath)
-        // useLedger, _ := cmd.Flags().GetBool(flags.FlagUseLedger)
-
-        // if len(hdPath) == 0 {
-        // 	hdPath = hd.CreateHDPath(coinType, account, index).String()
-        // } else if useLedger {
-        // 	return errors.New("cannot set custom bip32 path with ledger")
-        // }
-
-        // // If we're using ledger, only thing we need is the path and the bech32 prefix.
-        // if useLedger {
-        // 	bech32PrefixAccAddr := sdk.GetConfig().GetBech32AccountAddrPrefix()
-        // 	k, err := kb.SaveLedgerKey(name, hd.Secp256k1, bech32PrefixAccAddr, coinType, account, index)
-        // 	if err != nil {
-        // 		return err
-        // 	}
-
-        // 	return printCreate(cmd, k, false, "", outputFormat)
-        // }
-
-        // // Get bip39 mnemonic
-        // var mnemonic, bip39Passphrase string
-
-        // recover, _ := cmd.Flags().GetBool(flagRecover)
-        // if recover {
-        // 	mnemonic, err = input.GetString("Enter your bip39 mnemonic", inBuf)
-        // 	if err != nil {
-        // 		return err
-        // 	}
-
-        // 	if !bip39.IsMnemonicValid(mnemonic) {
-        // 		return errors.New("invalid mnemonic")
-        // 	}
-        // } else if interactive {
-        // 	mnemonic, err = input.GetString("Enter your bip39 mnemonic, or hit enter to generate one.", inBuf)
-        // 	if err != nil {
-        // 		return err
-        // 	}
-
-        // 	if !bip39.IsMnemonicValid(mnemonic) && mnemonic != "" {
-        // 		return errors.New("invalid mnemonic")
-        // 	}
-        // }
-
-        // if len(mnemonic) == 0 {
-        // 	// read entropy seed straight from cmtcrypto.Rand and convert to mnemonic
-        // 	entropySeed, err := bip39.NewEntropy(mnemonicEntropySize)
-        // 	if err != nil {
-        // 		return err
-        // 	}
-
-        // 	mnemonic, err = bip39.NewMnemonic(entropySeed)
-        // 	if err != nil {
-        // 		return err
-        // 	}
-        // }
-
-        // // override bip39 passphrase
-        // if interactive {
-        // 	bip39Passphrase, err = input.GetString(
-        // 		"Enter your bip39 passphrase. This is combined with the mnemonic to derive the seed. "+
-        // 			"Most users should just hit enter to use the default, \"\"", inBuf)
-        // 	if err != nil {
-        // 		return err
-        // 	}
-
-        // 	// if they use one, make them re-enter it
-        // 	if len(bip39Passphrase) != 0 {
-        // 		p2, err := input.GetString("Repeat the passphrase:", inBuf)
-        // 		if err != nil {
-        // 			return err
-        // 		}
-
-        // 		if bip39Passphrase != p2 {
-        // 			return errors.New("passphrases don't match")
-        // 		}
-        // 	}
-        // }
-
-        // k, err := kb.NewAccount(name, mnemonic, bip39Passphrase, hdPath, algo)
-        // if err != nil {
-        // 	return err
-        // }
-
-        // // Recover key from seed passphrase
-        // if recover {
-        // 	// Hide mnemonic from output
-        // 	showMnemonic = false
-        // 	mnemonic = ""
-        // }
-
-        // return printCreate(cmd, k, showMnemonic, mnemonic, outputFormat)
+        if let Err(err) = self.add() {
+            status_err!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+impl AddCmd {
+    fn add(&self) -> Result<(), Error> {
+        let config = APP.config();
+        let bech32_prefix = &config.chain.bech32_prefix;
+        let output = self.output.unwrap_or(config.global.output);
+        let name = &self.name;
+
+        let keyring: Box<dyn Keyring> = match self.dry_run {
+            // use in memory keybase
+            true => Box::new(MemoryKeyring::new()),
+            false => super::open_keyring(&config)?,
+        };
+
+        // gather every input before touching the keyring, so that a failure
+        // never destroys the key being overridden
+        let (record, mnemonic) = self.new_record(keyring.as_ref(), &config)?;
+
+        if keyring.exists(name)? {
+            // account exists, ask for user confirmation
+            let response =
+                input::get_confirmation(&format!("override the existing name {}", name))?;
+            if !response {
+                fail!(ErrorKind::Keyring, "aborted");
+            }
+
+            keyring.delete(name)?;
+        }
+
+        keyring.put(&record)?;
+
+        match &mnemonic {
+            Some(mnemonic) => print_create(
+                &record,
+                bech32_prefix,
+                !self.no_backup,
+                mnemonic.phrase(),
+                output,
+            ),
+            None => print_create(&record, bech32_prefix, false, "", output),
+        }
+    }
+
+    /// Build the record to store, along with the mnemonic it was derived
+    /// from when it was generated here
+    fn new_record(
+        &self,
+        keyring: &dyn Keyring,
+        config: &AmonConfig,
+    ) -> Result<(KeyRecord, Option<Mnemonic>), Error> {
+        let bech32_prefix = &config.chain.bech32_prefix;
+        let name = &self.name;
+
+        if !self.multisig.is_empty() {
+            let mut pks = self
+                .multisig
+                .iter()
+                .map(|keyname| Ok(keyring.get(keyname)?.pub_key))
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            if !self.nosort {
+                pks.sort_by_key(PubKey::address);
+            }
+
+            let pk = LegacyAminoPubKey::new(self.multisig_threshold, pks)?;
+            return Ok((KeyRecord::new_multi(name, PubKey::Multisig(pk)), None));
+        }
+
+        if let Some(pubkey) = &self.pubkey {
+            let json = serde_json::from_str(pubkey).map_err(|e| ErrorKind::Crypto.context(e))?;
+            let record = KeyRecord::new_offline(name, PubKey::from_json(&json)?);
+            return Ok((record, None));
+        }
+
+        let coin_type = self
+            .coin_type
+            .or(config.chain.coin_type)
+            .unwrap_or_else(|| self.algo.default_coin_type());
+        let hd_path = match &self.hd_path {
+            Some(hd_path) => hd_path.clone(),
+            // ed25519 keys only support hardened derivation
+            None if self.algo == SigningAlgo::Ed25519 => {
+                HdPath::bip44(coin_type, self.account, self.index)?.hardened()
+            }
+            None => HdPath::bip44(coin_type, self.account, self.index)?,
+        };
+
+        if self.ledger && self.hd_path.is_some() {
+            fail!(
+                ErrorKind::Ledger,
+                "cannot set custom bip32 path with ledger"
+            );
+        }
+
+        // If we're using ledger, only thing we need is the path and the bech32 prefix.
+        if self.ledger {
+            if self.algo != SigningAlgo::Secp256k1 {
+                fail!(ErrorKind::Ledger, "ledger only supports secp256k1 keys");
+            }
+
+            let path = hd_path.bip44_params()?;
+            let mut app = CosmosApp::connect()?;
+            let (pub_key, address) = app.get_address_pub_key(&path, bech32_prefix, true)?;
+            if address != address::pub_key_address(&pub_key, AddressKind::Acc, bech32_prefix)? {
+                fail!(
+                    ErrorKind::Ledger,
+                    "address {} returned by the device does not match its public key",
+                    address
+                );
+            }
+
+            return Ok((KeyRecord::new_ledger(name, pub_key, path), None));
+        }
+
+        let supplied = if self.recover {
+            let phrase = input::get_string("Enter your bip39 mnemonic")?;
+            Some(hd::parse_mnemonic(&phrase)?)
+        } else if self.interactive {
+            let phrase =
+                input::get_string("Enter your bip39 mnemonic, or hit enter to generate one.")?;
+            match phrase.is_empty() {
+                true => None,
+                false => Some(hd::parse_mnemonic(&phrase)?),
+            }
+        } else {
+            None
+        };
+
+        // override bip39 passphrase
+        let mut bip39_passphrase = String::new();
+        if self.interactive {
+            bip39_passphrase = input::get_password(
+                "Enter your bip39 passphrase. This is combined with the mnemonic to derive the seed. \
+                Most users should just hit enter to use the default, \"\"",
+            )?;
+
+            // if they use one, make them re-enter it
+            if !bip39_passphrase.is_empty() {
+                let p2 = input::get_password("Repeat the passphrase:")?;
+                if bip39_passphrase != p2 {
+                    fail!(ErrorKind::Crypto, "passphrases don't match");
+                }
+            }
+        }
+
+        // never echo back a mnemonic the user supplied
+        let (mnemonic, generated) = match supplied {
+            Some(mnemonic) => (mnemonic, false),
+            None => (hd::new_mnemonic(), true),
+        };
+
+        let priv_key = hd::derive(self.algo, &mnemonic, &bip39_passphrase, &hd_path)?;
+        let record = KeyRecord::new_local(name, priv_key);
+
+        Ok((record, Some(mnemonic).filter(|_| generated)))
     }
 }
 
@@ -292,28 +277,18 @@ impl config::Override<AmonConfig> for AddCmd {
     }
 }
 
-use serde_json::json;
-use std::fmt;
-use std::io::{Error, Write};
-
-enum OutputFormat {
-    Text,
-    JSON,
-}
-
 fn print_create(
-    k: String,
+    k: &KeyRecord,
+    bech32_prefix: &str,
     show_mnemonic: bool,
     mnemonic: &str,
     output_format: OutputFormat,
 ) -> Result<(), Error> {
+    let mut out = mk_acc_key_output(k, bech32_prefix)?;
+
     match output_format {
-        OutputFormat::Text => {
-            // if let Err(e) =
-            //     print_keyring_record(cmd.out_or_stdout(), k, mk_acc_key_output, &output_format)
-            // {
-            //     return Err(e);
-            // }
+        OutputFormat::Text | OutputFormat::Yaml => {
+            print_key_output(&out, output_format)?;
 
             // print mnemonic unless requested not to.
             if show_mnemonic {
@@ -324,15 +299,12 @@ fn print_create(
                 );
             }
         }
-        OutputFormat::JSON => {
-            // let mut out = mk_acc_key_output(k)?;
-
-            // if show_mnemonic {
-            //     out.mnemonic = mnemonic.to_string();
-            // }
+        OutputFormat::Json => {
+            if show_mnemonic {
+                out.mnemonic = mnemonic.to_string();
+            }
 
-            // let json_string = json!(&out).to_string();
-            // println!("{}", json_string);
+            print_key_output(&out, output_format)?;
         }
     }
     Ok(())
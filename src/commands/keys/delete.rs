@@ -1,26 +1,92 @@
-//! `delete` subcommand - example of how to write a subcommand
+//! `delete` subcommand - delete keys
 
 /// App-local prelude includes `app_reader()`/`app_writer()`/`app_config()`
 /// accessors along with logging macros. Customize as you see fit.
 use crate::prelude::*;
 
+use super::input;
 use crate::config::AmonConfig;
+use crate::error::{Error, ErrorKind};
+use crate::keyring::{KeyItem, Keyring};
 use abscissa_core::{config, Command, FrameworkError, Runnable};
+use std::process;
 
-/// delete subcommand
+/// delete subcommand - Delete the given keys
 ///
-/// The `Parser` proc macro generates an option parser based on the struct
-/// definition, and is defined in the `clap` crate. See their documentation
-/// for a more comprehensive example:
+/// Delete keys from the keyring. Both the key and its address index entry
+/// are removed.
 ///
-/// <https://docs.rs/clap/>
+/// Note that removing offline or ledger keys will remove
+/// only the public key references stored locally, i.e.
+/// private keys stored in a ledger device cannot be deleted with the CLI.
+/// Such references are only removed when --force is given.
 #[derive(clap::Parser, Command, Debug)]
-pub struct DeleteCmd {}
+pub struct DeleteCmd {
+    /// Names of the keys to delete
+    #[arg(required = true)]
+    pub names: Vec<String>,
+    /// Skip confirmation prompt when deleting keys
+    #[arg(short, long, default_value_t = false)]
+    pub yes: bool,
+    /// Also remove offline and ledger key references
+    #[arg(short, long, default_value_t = false)]
+    pub force: bool,
+}
 
 impl Runnable for DeleteCmd {
     /// Start the application.
     fn run(&self) {
-        println!("delete subcommand!");
+        let keyring = match super::open_keyring(&APP.config()) {
+            Ok(keyring) => keyring,
+            Err(err) => {
+                status_err!("{}", err);
+                process::exit(1);
+            }
+        };
+
+        // report every key, failing only once all were processed
+        let mut failed = false;
+        for name in &self.names {
+            match self.delete(keyring.as_ref(), name) {
+                Ok(result) => eprintln!("{}: {}", name, result),
+                Err(err) => {
+                    status_err!("{}: {}", name, err);
+                    failed = true;
+                }
+            }
+        }
+
+        if failed {
+            process::exit(1);
+        }
+    }
+}
+
+impl DeleteCmd {
+    /// Delete the key `name`, returning a description of what was done
+    fn delete(&self, keyring: &dyn Keyring, name: &str) -> Result<&'static str, Error> {
+        let record = keyring.get(name)?;
+        let reference = matches!(record.item, KeyItem::Ledger(_) | KeyItem::Offline);
+
+        if reference && !self.force {
+            fail!(
+                ErrorKind::Keyring,
+                "{} key reference not deleted, use --force to remove it",
+                record.key_type()
+            );
+        }
+
+        // confirm deletion, unless -y is passed
+        if !self.yes && !input::get_confirmation("Key reference will be deleted. Continue?")? {
+            return Ok("skipped");
+        }
+
+        keyring.delete(name)?;
+
+        if reference {
+            return Ok("Public key reference deleted");
+        }
+        Ok("Key deleted forever (uh oh!)")
     }
 }
 
@@ -1,26 +1,98 @@
-//! `export` subcommand - example of how to write a subcommand
+//! `export` subcommand - export private keys
 
 /// App-local prelude includes `app_reader()`/`app_writer()`/`app_config()`
 /// accessors along with logging macros. Customize as you see fit.
 use crate::prelude::*;
 
+use super::input;
 use crate::config::AmonConfig;
+use crate::crypto::{armor, PrivKey};
+use crate::error::{Error, ErrorKind};
+use crate::keyring::{KeyItem, Keyring};
 use abscissa_core::{config, Command, FrameworkError, Runnable};
+use std::process;
 
-/// export subcommand
+/// export subcommand - Export private keys
 ///
-/// The `Parser` proc macro generates an option parser based on the struct
-/// definition, and is defined in the `clap` crate. See their documentation
-/// for a more comprehensive example:
+/// Export a private key from the local keyring in ASCII-armored encrypted format.
 ///
-/// <https://docs.rs/clap/>
+/// When both the --unarmored-hex and --unsafe flags are selected, cryptographic
+/// private key material is exported in an INSECURE fashion that is designed to
+/// allow users to import their keys in hot wallets. This feature is for advanced
+/// users only that are confident about how to handle private keys work and are
+/// FULLY AWARE OF THE RISKS. If you are unsure, you may want to do some research
+/// and export your keys in ASCII-armored encrypted format.
 #[derive(clap::Parser, Command, Debug)]
-pub struct ExportCmd {}
+pub struct ExportCmd {
+    /// Name of the key to export
+    pub name: String,
+    /// Export unarmored hex privkey. Requires --unsafe.
+    #[arg(long, default_value_t = false)]
+    pub unarmored_hex: bool,
+    /// Enable unsafe operations. This flag must be switched on along with all unsafe operation-specific options.
+    #[arg(long, default_value_t = false)]
+    pub r#unsafe: bool,
+}
 
 impl Runnable for ExportCmd {
     /// Start the application.
     fn run(&self) {
-        println!("export subcommand!");
+        if let Err(err) = self.export() {
+            status_err!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+impl ExportCmd {
+    fn export(&self) -> Result<(), Error> {
+        if self.unarmored_hex != self.r#unsafe {
+            fail!(
+                ErrorKind::Keyring,
+                "the flags --unsafe and --unarmored-hex must be used together"
+            );
+        }
+
+        let keyring = super::open_keyring(&APP.config())?;
+
+        if self.unarmored_hex {
+            return self.export_unsafe_unarmored(keyring.as_ref());
+        }
+
+        let priv_key = local_priv_key(keyring.as_ref(), &self.name)?;
+        let passphrase = input::get_password("Enter passphrase to encrypt the exported key:")?;
+
+        println!("{}", armor::encrypt_armor_priv_key(&priv_key, &passphrase)?);
+        Ok(())
+    }
+
+    fn export_unsafe_unarmored(&self, keyring: &dyn Keyring) -> Result<(), Error> {
+        let priv_key = local_priv_key(keyring, &self.name)?;
+
+        if !input::get_confirmation(
+            "WARNING: The private key will be exported as an unarmored hexadecimal string. \
+             USE AT YOUR OWN RISK. Continue?",
+        )? {
+            return Ok(());
+        }
+
+        println!("{}", hex::encode(priv_key.to_bytes()));
+        Ok(())
+    }
+}
+
+/// Private key of the local key stored under `name`
+fn local_priv_key(keyring: &dyn Keyring, name: &str) -> Result<PrivKey, Error> {
+    let record = keyring.get(name)?;
+
+    match record.item {
+        KeyItem::Local(priv_key) => Ok(priv_key),
+        _ => fail!(
+            ErrorKind::Keyring,
+            "{}: cannot export the private key of a {} key",
+            name,
+            record.key_type()
+        ),
     }
 }
 
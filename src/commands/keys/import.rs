@@ -1,29 +1,63 @@
-//! `import` subcommand - example of how to write a subcommand
+//! `import` subcommand - import ASCII-armored private keys
 
 /// App-local prelude includes `app_reader()`/`app_writer()`/`app_config()`
 /// accessors along with logging macros. Customize as you see fit.
 use crate::prelude::*;
 
+use super::input;
 use crate::config::AmonConfig;
+use crate::crypto::{armor, PrivKey};
+use crate::error::{Error, ErrorKind};
+use crate::keyring::{KeyRecord, Keyring};
 use abscissa_core::{config, Command, FrameworkError, Runnable};
+use std::{fs, path::PathBuf, process};
 
-/// import subcommand
+/// import subcommand - Import private keys into the local keybase
 ///
-/// The `Parser` proc macro generates an option parser based on the struct
-/// definition, and is defined in the `clap` crate. See their documentation
-/// for a more comprehensive example:
-///
-/// <https://docs.rs/clap/>
+/// Import a ASCII armored private key into the local keybase.
 #[derive(clap::Parser, Command, Debug)]
-pub struct ImportCmd {}
+pub struct ImportCmd {
+    /// Name to store the key under
+    pub name: String,
+    /// File holding the ASCII-armored key, as written by `keys export`
+    pub keyfile: PathBuf,
+}
 
 impl Runnable for ImportCmd {
     /// Start the application.
     fn run(&self) {
-        println!("import subcommand!");
+        if let Err(err) = self.import() {
+            status_err!("{}", err);
+            process::exit(1);
+        }
     }
 }
 
+impl ImportCmd {
+    fn import(&self) -> Result<(), Error> {
+        let keyring = super::open_keyring(&APP.config())?;
+        let armor = fs::read_to_string(&self.keyfile)?;
+
+        let passphrase = input::get_password("Enter passphrase to decrypt your key:")?;
+        let priv_key = armor::unarmor_decrypt_priv_key(&armor, &passphrase)?;
+
+        import_priv_key(keyring.as_ref(), &self.name, priv_key)
+    }
+}
+
+/// Store `priv_key` under `name`, refusing to replace an existing key
+pub(super) fn import_priv_key(
+    keyring: &dyn Keyring,
+    name: &str,
+    priv_key: PrivKey,
+) -> Result<(), Error> {
+    if keyring.exists(name)? {
+        fail!(ErrorKind::Keyring, "cannot overwrite key: {}", name);
+    }
+
+    keyring.put(&KeyRecord::new_local(name, priv_key))
+}
+
 impl config::Override<AmonConfig> for ImportCmd {
     // Process the given command line options, overriding settings from
     // a configuration file using explicit flags taken from command-line
@@ -0,0 +1,59 @@
+//! `import-hex` subcommand - import raw hex private keys
+
+/// App-local prelude includes `app_reader()`/`app_writer()`/`app_config()`
+/// accessors along with logging macros. Customize as you see fit.
+use crate::prelude::*;
+
+use super::import::import_priv_key;
+use crate::config::AmonConfig;
+use crate::crypto::{PrivKey, SigningAlgo};
+use crate::error::{Error, ErrorKind};
+use abscissa_core::{config, Command, FrameworkError, Runnable};
+use std::process;
+
+/// import-hex subcommand - Import private keys into the local keybase
+///
+/// Import hex encoded private key into the local keybase.
+/// Supported key-types can be obtained with:
+///     keys import-hex --help
+#[derive(clap::Parser, Command, Debug)]
+pub struct ImportHexCmd {
+    /// Name to store the key under
+    pub name: String,
+    /// Hex-encoded private key, optionally prefixed with 0x
+    pub hex: String,
+    /// Private key signing algorithm kind
+    #[arg(long, value_enum, default_value_t = SigningAlgo::Secp256k1)]
+    pub key_type: SigningAlgo,
+}
+
+impl Runnable for ImportHexCmd {
+    /// Start the application.
+    fn run(&self) {
+        if let Err(err) = self.import_hex() {
+            status_err!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+impl ImportHexCmd {
+    fn import_hex(&self) -> Result<(), Error> {
+        let keyring = super::open_keyring(&APP.config())?;
+
+        let hex = self.hex.strip_prefix("0x").unwrap_or(&self.hex);
+        let bytes = hex::decode(hex).map_err(|e| ErrorKind::Crypto.context(e))?;
+        let priv_key = PrivKey::from_bytes(self.key_type, &bytes)?;
+
+        import_priv_key(keyring.as_ref(), &self.name, priv_key)
+    }
+}
+
+impl config::Override<AmonConfig> for ImportHexCmd {
+    // Process the given command line options, overriding settings from
+    // a configuration file using explicit flags taken from command-line
+    // arguments.
+    fn override_config(&self, config: AmonConfig) -> Result<AmonConfig, FrameworkError> {
+        Ok(config)
+    }
+}
@@ -0,0 +1,44 @@
+//! Helpers for prompting the user on the terminal
+
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Read a password without echoing it when stdin is a terminal, or a plain
+/// line when input is piped in.
+pub fn get_password(prompt: &str) -> io::Result<String> {
+    if io::stdin().is_terminal() {
+        return rpassword::prompt_password(format!("{} ", prompt));
+    }
+
+    eprintln!("{}", prompt);
+    read_line()
+}
+
+/// Prompt on stderr and read a line from stdin, trimmed of surrounding
+/// whitespace
+pub fn get_string(prompt: &str) -> io::Result<String> {
+    eprintln!("{}", prompt);
+    Ok(read_line()?.trim().to_owned())
+}
+
+/// Ask a yes/no question on stderr and read the answer from stdin.
+///
+/// Anything but an answer starting with `y` counts as "no".
+pub fn get_confirmation(prompt: &str) -> io::Result<bool> {
+    eprint!("{} [y/N]: ", prompt);
+    io::stderr().flush()?;
+
+    let mut response = String::new();
+    io::stdin().lock().read_line(&mut response)?;
+
+    Ok(response.trim().to_lowercase().starts_with('y'))
+}
+
+/// Read a line from stdin without its trailing newline
+fn read_line() -> io::Result<String> {
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    Ok(line.trim_end_matches(&['\r', '\n'][..]).to_owned())
+}
@@ -1,26 +1,64 @@
-//! `list` subcommand - example of how to write a subcommand
+//! `list` subcommand - list all keys
 
 /// App-local prelude includes `app_reader()`/`app_writer()`/`app_config()`
 /// accessors along with logging macros. Customize as you see fit.
 use crate::prelude::*;
 
+use super::output::{mk_acc_key_output, print_key_outputs, OutputFormat};
 use crate::config::AmonConfig;
+use crate::error::Error;
 use abscissa_core::{config, Command, FrameworkError, Runnable};
+use std::process;
 
-/// list subcommand
+/// list subcommand - List all keys
 ///
-/// The `Parser` proc macro generates an option parser based on the struct
-/// definition, and is defined in the `clap` crate. See their documentation
-/// for a more comprehensive example:
-///
-/// <https://docs.rs/clap/>
+/// Return a list of all public keys stored by this key manager
+/// along with their associated name and address.
 #[derive(clap::Parser, Command, Debug)]
-pub struct ListCmd {}
+pub struct ListCmd {
+    /// List names only
+    #[arg(short = 'n', long, default_value_t = false)]
+    pub list_names: bool,
+    /// Output format, defaults to the `output` setting of the configuration
+    #[arg(short, long, value_enum)]
+    pub output: Option<OutputFormat>,
+}
 
 impl Runnable for ListCmd {
     /// Start the application.
     fn run(&self) {
-        println!("list subcommand!");
+        if let Err(err) = self.list() {
+            status_err!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+impl ListCmd {
+    fn list(&self) -> Result<(), Error> {
+        let config = APP.config();
+        let records = super::open_keyring(&config)?.list()?;
+        let output = self.output.unwrap_or(config.global.output);
+
+        // structured formats still print an empty list for scripts to parse
+        if records.is_empty() && output == OutputFormat::Text {
+            eprintln!("No records were found in keyring");
+            return Ok(());
+        }
+
+        if self.list_names {
+            for record in &records {
+                println!("{}", record.name);
+            }
+            return Ok(());
+        }
+
+        let outs = records
+            .iter()
+            .map(|record| mk_acc_key_output(record, &config.chain.bech32_prefix))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        print_key_outputs(&outs, output)
     }
 }
 
@@ -1,26 +1,55 @@
-//! `migrate` subcommand - example of how to write a subcommand
+//! `migrate` subcommand - migrate legacy key records
 
 /// App-local prelude includes `app_reader()`/`app_writer()`/`app_config()`
 /// accessors along with logging macros. Customize as you see fit.
 use crate::prelude::*;
 
 use crate::config::AmonConfig;
+use crate::error::{Error, ErrorKind};
 use abscissa_core::{config, Command, FrameworkError, Runnable};
+use std::process;
 
-/// migrate subcommand
+/// migrate subcommand - Migrate keys from amino to proto serialization format
 ///
-/// The `Parser` proc macro generates an option parser based on the struct
-/// definition, and is defined in the `clap` crate. See their documentation
-/// for a more comprehensive example:
-///
-/// <https://docs.rs/clap/>
+/// Migrate keys from Amino to Protocol Buffers records.
+/// For each key material entry, the command will check if the key can be deserialized using proto.
+/// If this is the case, the key is already migrated. Therefore, we skip it and continue with a next one.
+/// Otherwise, we try to deserialize it using Amino into LegacyInfo. If this attempt is successful, we serialize
+/// LegacyInfo to Protobuf serialization format and overwrite the keyring entry. If any error occurred, it will be
+/// outputted in CLI and migration will be continued until all keys in the keyring DB are exhausted.
 #[derive(clap::Parser, Command, Debug)]
 pub struct MigrateCmd {}
 
 impl Runnable for MigrateCmd {
     /// Start the application.
     fn run(&self) {
-        println!("migrate subcommand!");
+        if let Err(err) = self.migrate() {
+            status_err!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+impl MigrateCmd {
+    fn migrate(&self) -> Result<(), Error> {
+        let keyring = super::open_keyring(&APP.config())?;
+
+        let mut failed = 0;
+        for name in keyring.names()? {
+            match keyring.migrate(&name) {
+                Ok(true) => eprintln!("{}: migrated", name),
+                Ok(false) => eprintln!("{}: skipped, already migrated", name),
+                Err(err) => {
+                    status_err!("{}: {}", name, err);
+                    failed += 1;
+                }
+            }
+        }
+
+        if failed > 0 {
+            fail!(ErrorKind::Keyring, "{} keys could not be migrated", failed);
+        }
+        Ok(())
     }
 }
 
@@ -1,26 +1,71 @@
-//! `mnemonic` subcommand - example of how to write a subcommand
+//! `mnemonic` subcommand - generate BIP39 mnemonics
 
 /// App-local prelude includes `app_reader()`/`app_writer()`/`app_config()`
 /// accessors along with logging macros. Customize as you see fit.
 use crate::prelude::*;
 
+use super::input;
 use crate::config::AmonConfig;
+use crate::error::{Error, ErrorKind};
+use crate::hd;
 use abscissa_core::{config, Command, FrameworkError, Runnable};
+use sha2::{Digest, Sha256};
+use std::process;
 
-/// mnemonic subcommand
-///
-/// The `Parser` proc macro generates an option parser based on the struct
-/// definition, and is defined in the `clap` crate. See their documentation
-/// for a more comprehensive example:
+/// Minimum length of user-supplied entropy: 256 bits in base64
+const MIN_ENTROPY_INPUT_LEN: usize = 43;
+
+/// mnemonic subcommand - Compute the bip39 mnemonic for some input entropy
 ///
-/// <https://docs.rs/clap/>
+/// Create a bip39 mnemonic, sometimes called a seed phrase, by reading from the system
+/// entropy. To pass your own entropy, use --unsafe-entropy
 #[derive(clap::Parser, Command, Debug)]
-pub struct MnemonicCmd {}
+pub struct MnemonicCmd {
+    /// Prompt the user to supply their own entropy, instead of relying on the system
+    #[arg(long, default_value_t = false)]
+    pub unsafe_entropy: bool,
+}
 
 impl Runnable for MnemonicCmd {
     /// Start the application.
     fn run(&self) {
-        println!("mnemonic subcommand!");
+        if let Err(err) = self.mnemonic() {
+            status_err!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+impl MnemonicCmd {
+    fn mnemonic(&self) -> Result<(), Error> {
+        let mnemonic = if self.unsafe_entropy {
+            // prompt the user to enter some entropy
+            let input_entropy = input::get_string(
+                "> WARNING: Generate at least 256-bits of entropy and enter the results here:",
+            )?;
+
+            let len = input_entropy.len();
+            if len < MIN_ENTROPY_INPUT_LEN {
+                fail!(
+                    ErrorKind::Crypto,
+                    "256-bits is 43 characters in Base-64, and 100 in Base-6. \
+                     You entered {}, and probably want more",
+                    len
+                );
+            }
+
+            if !input::get_confirmation(&format!("> Input length: {}", len))? {
+                return Ok(());
+            }
+
+            // hash input entropy to get entropy seed
+            hd::mnemonic_from_entropy(&Sha256::digest(input_entropy.as_bytes()))?
+        } else {
+            hd::new_mnemonic()
+        };
+
+        println!("{}", mnemonic.phrase());
+        Ok(())
     }
 }
 
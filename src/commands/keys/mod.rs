@@ -4,16 +4,28 @@
 /// accessors along with logging macros. Customize as you see fit.
 use crate::prelude::*;
 
+use crate::commands::default_home_dir;
 use crate::config::AmonConfig;
+use crate::error::{Error, ErrorKind};
+#[cfg(target_os = "linux")]
+use crate::keyring::KWalletKeyring;
+use crate::keyring::{
+    FileKeyring, Keyring, KeyringBackend, MemoryKeyring, OsKeyring, PassKeyring,
+    DEFAULT_PASS_PREFIX, SERVICE_NAME,
+};
 use abscissa_core::{config, Command, FrameworkError, Runnable};
+use std::path::PathBuf;
 
 mod add;
 mod delete;
 mod export;
 mod import;
+mod import_hex;
+mod input;
 mod list;
 mod migrate;
 mod mnemonic;
+mod output;
 mod parse;
 mod rename;
 mod show;
@@ -28,33 +40,46 @@ mod show;
 ///
 /// The keyring supports the following backends:
 ///
-///     os          Uses the operating system's default credentials store.
+/// ```text
+/// os          Uses the operating system's default credentials store.
 ///
-///     file        Uses encrypted file-based keystore within the app's configuration directory.
-///                 This keyring will request a password each time it is accessed, which may occur
-///                 multiple times in a single command resulting in repeated password prompts.
+/// file        Uses encrypted file-based keystore within the app's configuration directory.
+///             This keyring will request a password each time it is accessed, which may occur
+///             multiple times in a single command resulting in repeated password prompts.
 ///
-///     kwallet     Uses KDE Wallet Manager as a credentials management application.
+/// kwallet     Uses KDE Wallet Manager as a credentials management application.
 ///
-///     pass        Uses the pass command line utility to store and retrieve keys.
+/// pass        Uses the pass command line utility to store and retrieve keys.
 ///
-///     test        Stores keys insecurely to disk. It does not prompt for a password to be unlocked
-///                 and it should be use only for testing purposes.
+/// test        Stores keys insecurely to disk. It does not prompt for a password to be unlocked
+///             and it should be use only for testing purposes.
+///
+/// memory      Keeps keys in memory only; they are discarded when the command exits.
+/// ```
 ///
 /// kwallet and pass backends depend on external tools. Refer to their respective documentation for more
 /// information:
 ///
-///     KWallet     https://github.com/KDE/kwallet
+/// ```text
+/// KWallet     https://github.com/KDE/kwallet
+///
+/// pass        https://www.passwordstore.org/
+/// ```
 ///
-///     pass        https://www.passwordstore.org/
+/// The pass backend requires GnuPG: https://gnupg.org/ and stores its entries under the
+/// `pass_prefix` of the `[global]` configuration section (keyring-amon by default).
 ///
-/// The pass backend requires GnuPG: https://gnupg.org/
+/// The backend is selected with the --keyring-backend flag or the `keyring_backend` setting of
+/// the `[global]` configuration section, and defaults to os. File-based keyrings live in the
+/// keyring-<backend> directory under --keyring-dir, which defaults to the home directory given
+/// with --home or $AMON_HOME, or ~/.amon.
 #[derive(clap::Subcommand, Command, Debug)]
 pub enum KeysCmd {
     Add(add::AddCmd),
     Delete(delete::DeleteCmd),
     Export(export::ExportCmd),
     Import(import::ImportCmd),
+    ImportHex(import_hex::ImportHexCmd),
     List(list::ListCmd),
     Migrate(migrate::MigrateCmd),
     Mnemonic(mnemonic::MnemonicCmd),
@@ -66,12 +91,12 @@ pub enum KeysCmd {
 impl Runnable for KeysCmd {
     /// Start the application.
     fn run(&self) {
-        println!("Hello, amon wallet!");
         match self {
             KeysCmd::Add(c) => c.run(),
             KeysCmd::Delete(c) => c.run(),
             KeysCmd::Export(c) => c.run(),
             KeysCmd::Import(c) => c.run(),
+            KeysCmd::ImportHex(c) => c.run(),
             KeysCmd::List(c) => c.run(),
             KeysCmd::Migrate(c) => c.run(),
             KeysCmd::Mnemonic(c) => c.run(),
@@ -82,6 +107,51 @@ impl Runnable for KeysCmd {
     }
 }
 
+/// Open the keyring backend selected in the application configuration
+fn open_keyring(config: &AmonConfig) -> Result<Box<dyn Keyring>, Error> {
+    match config.global.keyring_backend {
+        KeyringBackend::Os => Ok(Box::new(OsKeyring::new(SERVICE_NAME))),
+        KeyringBackend::File => Ok(Box::new(FileKeyring::new(
+            keyring_dir(config)?.join(FileKeyring::DIR_NAME),
+            Box::new(input::get_password),
+        ))),
+        KeyringBackend::Test => Ok(Box::new(FileKeyring::new_test(
+            keyring_dir(config)?.join(FileKeyring::TEST_DIR_NAME),
+        ))),
+        KeyringBackend::Pass => Ok(Box::new(PassKeyring::new(
+            None,
+            config
+                .global
+                .pass_prefix
+                .as_deref()
+                .unwrap_or(DEFAULT_PASS_PREFIX),
+        )?)),
+        KeyringBackend::Memory => Ok(Box::new(MemoryKeyring::new())),
+        #[cfg(target_os = "linux")]
+        KeyringBackend::Kwallet => Ok(Box::new(KWalletKeyring::open(
+            KWalletKeyring::DEFAULT_WALLET,
+            "",
+            SERVICE_NAME,
+        )?)),
+        #[cfg(not(target_os = "linux"))]
+        KeyringBackend::Kwallet => fail!(
+            ErrorKind::Keyring,
+            "kwallet keyring backend is only supported on Linux"
+        ),
+    }
+}
+
+/// Directory holding the file-based keyrings
+fn keyring_dir(config: &AmonConfig) -> Result<PathBuf, Error> {
+    match config.global.keyring_dir.clone().or_else(default_home_dir) {
+        Some(dir) => Ok(dir),
+        None => fail!(
+            ErrorKind::Keyring,
+            "could not find home directory, please specify --home or --keyring-dir"
+        ),
+    }
+}
+
 impl config::Override<AmonConfig> for KeysCmd {
     // Process the given command line options, overriding settings from
     // a configuration file using explicit flags taken from command-line
@@ -0,0 +1,78 @@
+//! Key output shared by the `keys` subcommands, mirroring the SDK
+//! `KeyOutput` and its text/JSON rendering.
+
+use crate::address::{self, AddressKind};
+pub use crate::config::OutputFormat;
+use crate::error::{Error, ErrorKind};
+use crate::hd::HdPath;
+use crate::keyring::{KeyItem, KeyRecord};
+use serde::Serialize;
+
+/// Key description
+#[derive(Clone, Debug, Serialize)]
+pub struct KeyOutput {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub key_type: String,
+    pub address: String,
+    pub pubkey: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub mnemonic: String,
+}
+
+/// Describe `record` by its address of the given kind
+pub fn mk_key_output(
+    record: &KeyRecord,
+    kind: AddressKind,
+    bech32_prefix: &str,
+) -> Result<KeyOutput, Error> {
+    Ok(KeyOutput {
+        name: record.name.clone(),
+        key_type: record.key_type().to_string(),
+        address: address::pub_key_address(&record.pub_key, kind, bech32_prefix)?,
+        pubkey: record.pub_key.to_json().to_string(),
+        path: match &record.item {
+            KeyItem::Ledger(params) => Some(HdPath::from(params).to_string()),
+            _ => None,
+        },
+        mnemonic: String::new(),
+    })
+}
+
+/// Describe `record` by its account address
+pub fn mk_acc_key_output(record: &KeyRecord, bech32_prefix: &str) -> Result<KeyOutput, Error> {
+    mk_key_output(record, AddressKind::Acc, bech32_prefix)
+}
+
+/// Print a single key; JSON prints an object while text and YAML print a
+/// one-element list
+pub fn print_key_output(out: &KeyOutput, output_format: OutputFormat) -> Result<(), Error> {
+    match output_format {
+        OutputFormat::Json => print_json(out),
+        OutputFormat::Text | OutputFormat::Yaml => print_yaml(&[out]),
+    }
+}
+
+/// Print a list of keys
+pub fn print_key_outputs(outs: &[KeyOutput], output_format: OutputFormat) -> Result<(), Error> {
+    match output_format {
+        OutputFormat::Json => print_json(&outs),
+        OutputFormat::Text | OutputFormat::Yaml => print_yaml(&outs),
+    }
+}
+
+/// Print `value` as a single line of JSON
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<(), Error> {
+    let json = serde_json::to_string(value).map_err(|e| ErrorKind::Io.context(e))?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Print `value` as a YAML document, without the leading `---`
+pub fn print_yaml<T: Serialize + ?Sized>(value: &T) -> Result<(), Error> {
+    let yaml = serde_yaml::to_string(value).map_err(|e| ErrorKind::Io.context(e))?;
+    print!("{}", yaml.trim_start_matches("---\n"));
+    Ok(())
+}
@@ -1,26 +1,87 @@
-//! `parse` subcommand - example of how to write a subcommand
+//! `parse` subcommand - convert addresses between hex and bech32
 
 /// App-local prelude includes `app_reader()`/`app_writer()`/`app_config()`
 /// accessors along with logging macros. Customize as you see fit.
 use crate::prelude::*;
 
+use super::output::{print_json, print_yaml, OutputFormat};
+use crate::address::{self, AddressKind};
 use crate::config::AmonConfig;
+use crate::error::{Error, ErrorKind};
 use abscissa_core::{config, Command, FrameworkError, Runnable};
+use serde::Serialize;
+use std::process;
 
-/// parse subcommand
+/// parse subcommand - Parse address from hex to bech32 and vice versa
 ///
-/// The `Parser` proc macro generates an option parser based on the struct
-/// definition, and is defined in the `clap` crate. See their documentation
-/// for a more comprehensive example:
-///
-/// <https://docs.rs/clap/>
+/// Convert and print to stdout key addresses and fingerprints from
+/// hexadecimal into bech32 prefixed format and vice versa.
 #[derive(clap::Parser, Command, Debug)]
-pub struct ParseCmd {}
+pub struct ParseCmd {
+    /// Hex or bech32 address
+    pub address: String,
+    /// Output format, defaults to the `output` setting of the configuration
+    #[arg(short, long, value_enum)]
+    pub output: Option<OutputFormat>,
+}
+
+/// Parsed address
+#[derive(Debug, Serialize)]
+struct ParseOutput {
+    /// Human-readable part of the bech32 input
+    #[serde(skip_serializing_if = "Option::is_none")]
+    human: Option<String>,
+    /// Upper-case hex address bytes
+    bytes: String,
+    /// Account, validator operator and consensus addresses
+    formats: Vec<String>,
+}
 
 impl Runnable for ParseCmd {
     /// Start the application.
     fn run(&self) {
-        println!("keys parse subcommand");
+        if let Err(err) = self.parse() {
+            status_err!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+impl ParseCmd {
+    fn parse(&self) -> Result<(), Error> {
+        let addr = self.address.trim();
+        if addr.is_empty() {
+            fail!(ErrorKind::Address, "couldn't parse empty input");
+        }
+
+        let (human, bytes) = match address::decode(addr) {
+            Ok((hrp, bytes)) => (Some(hrp), bytes),
+            Err(_) => match hex::decode(addr) {
+                Ok(bytes) => (None, bytes),
+                Err(_) => fail!(
+                    ErrorKind::Address,
+                    "couldn't find valid bech32 nor hex data"
+                ),
+            },
+        };
+
+        let config = APP.config();
+        let prefix = &config.chain.bech32_prefix;
+        let formats = [AddressKind::Acc, AddressKind::Val, AddressKind::Cons]
+            .iter()
+            .map(|kind| address::encode(&kind.hrp(prefix), &bytes))
+            .collect::<Result<_, _>>()?;
+
+        let out = ParseOutput {
+            human,
+            bytes: hex::encode_upper(&bytes),
+            formats,
+        };
+
+        match self.output.unwrap_or(config.global.output) {
+            OutputFormat::Json => print_json(&out),
+            OutputFormat::Text | OutputFormat::Yaml => print_yaml(&out),
+        }
     }
 }
 
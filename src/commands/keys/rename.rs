@@ -1,26 +1,70 @@
-//! `rename` subcommand - example of how to write a subcommand
+//! `rename` subcommand - rename a key
 
 /// App-local prelude includes `app_reader()`/`app_writer()`/`app_config()`
 /// accessors along with logging macros. Customize as you see fit.
 use crate::prelude::*;
 
+use super::input;
 use crate::config::AmonConfig;
+use crate::error::{Error, ErrorKind};
 use abscissa_core::{config, Command, FrameworkError, Runnable};
+use std::process;
 
-/// rename subcommand
+/// rename subcommand - Rename an existing key
 ///
-/// The `Parser` proc macro generates an option parser based on the struct
-/// definition, and is defined in the `clap` crate. See their documentation
-/// for a more comprehensive example:
-///
-/// <https://docs.rs/clap/>
+/// Rename a key from the keyring, keeping its address index entry pointing at
+/// it. An existing key under the new name is only replaced after confirmation.
 #[derive(clap::Parser, Command, Debug)]
-pub struct RenameCmd {}
+pub struct RenameCmd {
+    /// Current name of the key
+    pub old_name: String,
+    /// New name of the key
+    pub new_name: String,
+    /// Skip confirmation prompt when replacing an existing key
+    #[arg(short, long, default_value_t = false)]
+    pub yes: bool,
+}
 
 impl Runnable for RenameCmd {
     /// Start the application.
     fn run(&self) {
-        println!("keys rename subcommand!");
+        if let Err(err) = self.rename() {
+            status_err!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+impl RenameCmd {
+    fn rename(&self) -> Result<(), Error> {
+        if self.old_name == self.new_name {
+            fail!(
+                ErrorKind::Keyring,
+                "{}: new name must differ",
+                self.new_name
+            );
+        }
+
+        let keyring = super::open_keyring(&APP.config())?;
+
+        // make sure the key exists before asking anything
+        keyring.get(&self.old_name)?;
+
+        if keyring.exists(&self.new_name)? {
+            let prompt = format!("override the existing name {}", self.new_name);
+            if !self.yes && !input::get_confirmation(&prompt)? {
+                fail!(ErrorKind::Keyring, "aborted");
+            }
+
+            keyring.delete(&self.new_name)?;
+        }
+
+        keyring.rename(&self.old_name, &self.new_name)?;
+        eprintln!(
+            "Key was successfully renamed from {} to {}",
+            self.old_name, self.new_name
+        );
+        Ok(())
     }
 }
 
@@ -1,29 +1,104 @@
-//! `show` subcommand - example of how to write a subcommand
+//! `show` subcommand - show key info
 
 /// App-local prelude includes `app_reader()`/`app_writer()`/`app_config()`
 /// accessors along with logging macros. Customize as you see fit.
 use crate::prelude::*;
 
+use super::output::{mk_key_output, print_key_output, OutputFormat};
+use crate::address::{self, AddressKind};
 use crate::config::AmonConfig;
+use crate::crypto::{LegacyAminoPubKey, PubKey};
+use crate::error::Error;
+use crate::keyring::{KeyRecord, Keyring};
 use abscissa_core::{config, Command, FrameworkError, Runnable};
+use std::process;
 
-/// show subcommand
-///
-/// The `Parser` proc macro generates an option parser based on the struct
-/// definition, and is defined in the `clap` crate. See their documentation
-/// for a more comprehensive example:
+/// Name of the ephemeral record composed from several keys
+const DEFAULT_MULTISIG_KEY_NAME: &str = "multi";
+
+/// show subcommand - Retrieve key information by name or address
 ///
-/// <https://docs.rs/clap/>
+/// Display keys details. If multiple names or addresses are provided,
+/// then an ephemeral multisig key will be created under the name "multi"
+/// consisting of all the keys provided by name and multisig threshold.
 #[derive(clap::Parser, Command, Debug)]
-pub struct ShowCmd {}
+pub struct ShowCmd {
+    /// Names or addresses of the keys to show
+    #[arg(required = true)]
+    pub names: Vec<String>,
+    /// Output the address only (cannot be used with --output)
+    #[arg(
+        short,
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["pubkey", "output"]
+    )]
+    pub address: bool,
+    /// The Bech32 prefix encoding for a key
+    #[arg(long, value_enum, default_value_t = AddressKind::Acc)]
+    pub bech: AddressKind,
+    /// K out of n required signatures
+    #[arg(long, default_value_t = 1)]
+    pub multisig_threshold: u32,
+    /// Output the public key only (cannot be used with --output)
+    #[arg(short, long, default_value_t = false, conflicts_with = "output")]
+    pub pubkey: bool,
+    /// Output format, defaults to the `output` setting of the configuration
+    #[arg(short, long, value_enum)]
+    pub output: Option<OutputFormat>,
+}
 
 impl Runnable for ShowCmd {
     /// Start the application.
     fn run(&self) {
-        println!("keys show command!");
+        if let Err(err) = self.show() {
+            status_err!("{}", err);
+            process::exit(1);
+        }
     }
 }
 
+impl ShowCmd {
+    fn show(&self) -> Result<(), Error> {
+        let config = APP.config();
+        let keyring = super::open_keyring(&config)?;
+
+        let record = if self.names.len() == 1 {
+            fetch_key(keyring.as_ref(), &self.names[0])?
+        } else {
+            let pks = self
+                .names
+                .iter()
+                .map(|name| Ok(fetch_key(keyring.as_ref(), name)?.pub_key))
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            let multikey = LegacyAminoPubKey::new(self.multisig_threshold, pks)?;
+            KeyRecord::new_multi(DEFAULT_MULTISIG_KEY_NAME, PubKey::Multisig(multikey))
+        };
+
+        let out = mk_key_output(&record, self.bech, &config.chain.bech32_prefix)?;
+
+        if self.address {
+            println!("{}", out.address);
+        } else if self.pubkey {
+            println!("{}", out.pubkey);
+        } else {
+            print_key_output(&out, self.output.unwrap_or(config.global.output))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Fetch a key by name, or else by bech32 address
+fn fetch_key(keyring: &dyn Keyring, key_ref: &str) -> Result<KeyRecord, Error> {
+    if keyring.exists(key_ref)? || address::decode(key_ref).is_err() {
+        return keyring.get(key_ref);
+    }
+
+    keyring.get_by_address(key_ref)
+}
+
 impl config::Override<AmonConfig> for ShowCmd {
     // Process the given command line options, overriding settings from
     // a configuration file using explicit flags taken from command-line
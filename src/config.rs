@@ -1,47 +1,281 @@
 //! Amon Config
 //!
+//! The configuration file follows the layout of the Hermes one: a `[global]`
+//! section with settings shared by all commands, and a `[[chains]]` table
+//! describing each chain amon can talk to.
+//!
+//! ```toml
+//! [global]
+//! keyring_backend = "file"
+//! output = "json"
+//! log_level = "info"
+//!
+//! [[chains]]
+//! id = "cosmoshub-4"
+//! bech32_prefix = "cosmos"
+//! coin_type = 118
+//! rpc_addr = "https://rpc.cosmos.network:443"
+//! grpc_addr = "https://grpc.cosmos.network:443"
+//! gas_price = { price = 0.025, denom = "uatom" }
+//! ```
+//!
 //! See instructions in `commands.rs` to specify the path to your
 //! application's configuration file and/or command-line options
 //! for specifying it.
 
+use crate::address::{self, DEFAULT_BECH32_PREFIX};
+use crate::error::{Error, ErrorKind};
+use crate::hd::ChildIndex;
+use crate::keyring::KeyringBackend;
+use abscissa_core::{fail, format_err};
 use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt, path::PathBuf};
 
 /// Amon Configuration
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct AmonConfig {
-    /// An example configuration section
-    pub account: Account,
+    /// Settings shared by all commands
+    #[serde(default)]
+    pub global: GlobalConfig,
+
+    /// Chains amon can talk to
+    #[serde(default)]
+    pub chains: Vec<ChainConfig>,
+
+    /// Chain the commands operate on, selected after the configuration is
+    /// loaded: the one given with `--chain`, the first configured chain, or
+    /// the Cosmos Hub defaults
+    #[serde(skip)]
+    pub chain: ChainConfig,
 }
 
-/// Default configuration settings.
-///
-/// Note: if your needs are as simple as below, you can
-/// use `#[derive(Default)]` on AmonConfig instead.
-impl Default for AmonConfig {
-    fn default() -> Self {
-        Self {
-            account: Account::default(),
+impl AmonConfig {
+    /// Settings of the chain with the given id
+    pub fn find_chain(&self, id: &str) -> Option<&ChainConfig> {
+        self.chains.iter().find(|chain| chain.id == id)
+    }
+
+    /// Check that every setting is usable
+    pub fn validate(&self) -> Result<(), Error> {
+        let mut ids = HashSet::new();
+        for chain in &self.chains {
+            chain.validate()?;
+            if !ids.insert(&chain.id) {
+                fail!(ErrorKind::Config, "duplicate chain id {}", chain.id);
+            }
         }
+        self.chain.validate()
     }
 }
 
-/// Example configuration section.
-///
-/// Delete this and replace it with your actual configuration structs.
+/// Settings shared by all commands
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct GlobalConfig {
+    /// Backend storing the keys: `os`, `file`, `kwallet`, `pass`, `test` or
+    /// `memory`
+    #[serde(default)]
+    pub keyring_backend: KeyringBackend,
+
+    /// Directory holding the file-based keyrings (the home directory by default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyring_dir: Option<PathBuf>,
+
+    /// Prefix of the `pass` backend entries (`keyring-amon` by default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pass_prefix: Option<String>,
+
+    /// Output format of the commands: `text`, `json` or `yaml`
+    #[serde(default)]
+    pub output: OutputFormat,
+
+    /// Verbosity of the logs: `trace`, `debug`, `info`, `warn` or `error`
+    #[serde(default)]
+    pub log_level: LogLevel,
+}
+
+/// Output format of the commands
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Human-readable text, rendered as YAML like the SDK does
+    #[default]
+    Text,
+    /// JSON
+    Json,
+    /// YAML
+    Yaml,
+}
+
+/// Verbosity of the logs
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    /// Everything, including tracing of internals
+    Trace,
+    /// Debugging information
+    Debug,
+    /// Informational messages
+    #[default]
+    Info,
+    /// Warnings and errors
+    Warn,
+    /// Errors only
+    Error,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        })
+    }
+}
+
+/// Chain settings
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
-pub struct Account {
-    /// Example configuration value
-    pub public_key: String,
-    pub private_key: String,
+pub struct ChainConfig {
+    /// Chain identifier, e.g. `cosmoshub-4`
+    pub id: String,
+
+    /// Human-readable prefix of account addresses, from which validator
+    /// operator and consensus prefixes are derived
+    pub bech32_prefix: String,
+
+    /// SLIP-0044 coin type used in HD derivation paths. When unset, keys are
+    /// derived with 118, or 60 for `eth_secp256k1` keys.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coin_type: Option<u32>,
+
+    /// Tendermint RPC endpoint
+    #[serde(default = "default_rpc_addr")]
+    pub rpc_addr: String,
+
+    /// gRPC endpoint
+    #[serde(default = "default_grpc_addr")]
+    pub grpc_addr: String,
+
+    /// Price of gas, in the denomination fees are paid in
+    pub gas_price: GasPrice,
 }
 
-impl Default for Account {
+impl Default for ChainConfig {
     fn default() -> Self {
         Self {
-            public_key: "alice public key".to_string(),
-            private_key: "alice private key".to_string(),
+            id: "cosmoshub-4".to_owned(),
+            bech32_prefix: DEFAULT_BECH32_PREFIX.to_owned(),
+            coin_type: None,
+            rpc_addr: default_rpc_addr(),
+            grpc_addr: default_grpc_addr(),
+            gas_price: GasPrice {
+                price: 0.025,
+                denom: "uatom".to_owned(),
+            },
+        }
+    }
+}
+
+impl ChainConfig {
+    /// Check that the chain settings are usable
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.id.trim().is_empty() {
+            fail!(ErrorKind::Config, "chain id must not be empty");
+        }
+
+        if self.bech32_prefix.to_lowercase() != self.bech32_prefix
+            || address::encode(&self.bech32_prefix, &[]).is_err()
+        {
+            fail!(
+                ErrorKind::Config,
+                "{}: invalid bech32 prefix {:?}",
+                self.id,
+                self.bech32_prefix
+            );
+        }
+
+        if let Some(coin_type) = self.coin_type {
+            ChildIndex::new(coin_type, true).map_err(|e| {
+                format_err!(ErrorKind::Config, "{}: invalid coin type: {}", self.id, e)
+            })?;
+        }
+
+        for (name, addr) in [("rpc_addr", &self.rpc_addr), ("grpc_addr", &self.grpc_addr)] {
+            if !is_url(addr) {
+                fail!(
+                    ErrorKind::Config,
+                    "{}: {} must be a URL, got {:?}",
+                    self.id,
+                    name,
+                    addr
+                );
+            }
+        }
+
+        self.gas_price
+            .validate()
+            .map_err(|e| format_err!(ErrorKind::Config, "{}: {}", self.id, e).into())
+    }
+}
+
+/// Gas price, e.g. `{ price = 0.025, denom = "uatom" }`
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct GasPrice {
+    /// Amount of `denom` paid per unit of gas
+    pub price: f64,
+
+    /// Denomination fees are paid in
+    pub denom: String,
+}
+
+impl GasPrice {
+    /// Check that the price is a non-negative amount of a valid SDK
+    /// denomination
+    pub fn validate(&self) -> Result<(), Error> {
+        if !self.price.is_finite() || self.price < 0.0 {
+            fail!(ErrorKind::Config, "invalid gas price {}", self.price);
+        }
+
+        // same rule as the SDK: [a-zA-Z][a-zA-Z0-9/:._-]{2,127}
+        let valid_denom = self.denom.len() >= 3
+            && self.denom.len() <= 128
+            && self.denom.starts_with(|c: char| c.is_ascii_alphabetic())
+            && self
+                .denom
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c));
+        if !valid_denom {
+            fail!(ErrorKind::Config, "invalid fee denom {:?}", self.denom);
+        }
+        Ok(())
+    }
+}
+
+fn default_rpc_addr() -> String {
+    "http://127.0.0.1:26657".to_owned()
+}
+
+fn default_grpc_addr() -> String {
+    "http://127.0.0.1:9090".to_owned()
+}
+
+/// Whether `addr` looks like `scheme://host[...]`
+fn is_url(addr: &str) -> bool {
+    match addr.split_once("://") {
+        Some((scheme, rest)) => {
+            !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '+')
+                && !rest.is_empty()
+                && !rest.starts_with('/')
         }
+        None => false,
     }
 }
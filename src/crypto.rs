@@ -0,0 +1,547 @@
+//! Public and private keys
+//!
+//! Key types mirror the Cosmos SDK `crypto/keys` packages. They convert to
+//! and from protobuf `Any` messages and the SDK JSON representation, and
+//! provide the legacy Amino encoding which some addresses are derived from.
+
+pub mod amino;
+pub mod armor;
+
+use crate::error::{Error, ErrorKind};
+use crate::hd;
+use abscissa_core::{fail, format_err};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use k256::ecdsa;
+use prost::Message;
+use ripemd::Ripemd160;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+use std::{convert::TryFrom, fmt};
+
+/// Type URL of secp256k1 public keys
+pub const SECP256K1_PUB_KEY_TYPE_URL: &str = "/cosmos.crypto.secp256k1.PubKey";
+
+/// Type URL of secp256k1 private keys
+pub const SECP256K1_PRIV_KEY_TYPE_URL: &str = "/cosmos.crypto.secp256k1.PrivKey";
+
+/// Type URL of ed25519 public keys
+pub const ED25519_PUB_KEY_TYPE_URL: &str = "/cosmos.crypto.ed25519.PubKey";
+
+/// Type URL of ed25519 private keys
+pub const ED25519_PRIV_KEY_TYPE_URL: &str = "/cosmos.crypto.ed25519.PrivKey";
+
+/// Type URL of Ethermint secp256k1 public keys
+pub const ETH_SECP256K1_PUB_KEY_TYPE_URL: &str = "/ethermint.crypto.v1.ethsecp256k1.PubKey";
+
+/// Type URL of Ethermint secp256k1 private keys
+pub const ETH_SECP256K1_PRIV_KEY_TYPE_URL: &str = "/ethermint.crypto.v1.ethsecp256k1.PrivKey";
+
+/// Type URL of legacy Amino multisig public keys
+pub const MULTISIG_PUB_KEY_TYPE_URL: &str = "/cosmos.crypto.multisig.LegacyAminoPubKey";
+
+/// Length of account addresses
+pub const ADDRESS_LEN: usize = 20;
+
+/// Signing algorithms of single keys
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum SigningAlgo {
+    /// secp256k1, the Cosmos SDK default
+    #[default]
+    Secp256k1,
+    /// ed25519
+    Ed25519,
+    /// secp256k1 with Ethereum (Keccak-256) addresses, as used by
+    /// Ethermint-based chains such as Evmos or Injective
+    #[value(name = "eth_secp256k1")]
+    EthSecp256k1,
+}
+
+impl SigningAlgo {
+    /// Name of the algorithm, as used by the SDK and in armor headers
+    pub fn name(&self) -> &'static str {
+        match self {
+            SigningAlgo::Secp256k1 => "secp256k1",
+            SigningAlgo::Ed25519 => "ed25519",
+            SigningAlgo::EthSecp256k1 => "eth_secp256k1",
+        }
+    }
+
+    /// Coin type wallets derive keys of this algorithm with, absent any
+    /// chain setting
+    pub fn default_coin_type(&self) -> u32 {
+        match self {
+            SigningAlgo::EthSecp256k1 => hd::ETH_COIN_TYPE,
+            SigningAlgo::Secp256k1 | SigningAlgo::Ed25519 => hd::DEFAULT_COIN_TYPE,
+        }
+    }
+}
+
+impl fmt::Display for SigningAlgo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Protobuf `google.protobuf.Any`
+#[derive(Clone, PartialEq, Message)]
+pub struct Any {
+    /// Type of the serialized message
+    #[prost(string, tag = "1")]
+    pub type_url: String,
+    /// Serialized message
+    #[prost(bytes = "vec", tag = "2")]
+    pub value: Vec<u8>,
+}
+
+/// Protobuf message of keys made of raw bytes
+#[derive(Clone, PartialEq, Message)]
+struct RawKey {
+    #[prost(bytes = "vec", tag = "1")]
+    key: Vec<u8>,
+}
+
+impl RawKey {
+    fn from_any(any: &Any) -> Result<Self, Error> {
+        RawKey::decode(any.value.as_slice()).map_err(|e| ErrorKind::Crypto.context(e).into())
+    }
+}
+
+/// Protobuf message of `LegacyAminoPubKey`
+#[derive(Clone, PartialEq, Message)]
+struct RawLegacyAminoPubKey {
+    #[prost(uint32, tag = "1")]
+    threshold: u32,
+    #[prost(message, repeated, tag = "2")]
+    public_keys: Vec<Any>,
+}
+
+/// Amino struct of `PubKeyMultisigThreshold`, following its prefix
+#[derive(Clone, PartialEq, Message)]
+struct RawAminoMultisig {
+    #[prost(uint32, tag = "1")]
+    threshold: u32,
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    public_keys: Vec<Vec<u8>>,
+}
+
+/// Public key
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PubKey {
+    /// secp256k1 public key
+    Secp256k1(ecdsa::VerifyingKey),
+    /// ed25519 public key
+    Ed25519(ed25519_dalek::VerifyingKey),
+    /// Ethermint secp256k1 public key
+    EthSecp256k1(ecdsa::VerifyingKey),
+    /// K-of-N threshold multisig public key
+    Multisig(LegacyAminoPubKey),
+}
+
+/// K-of-N threshold multisig public key, whose address is derived from its
+/// Amino encoding
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LegacyAminoPubKey {
+    /// Number of signatures required
+    pub threshold: u32,
+    /// Public keys of the participants
+    pub public_keys: Vec<PubKey>,
+}
+
+impl LegacyAminoPubKey {
+    /// K-of-N multisig key over `public_keys`, taken in the given order
+    pub fn new(threshold: u32, public_keys: Vec<PubKey>) -> Result<Self, Error> {
+        if threshold == 0 {
+            fail!(ErrorKind::Crypto, "threshold must be a positive integer");
+        }
+        if public_keys.len() < threshold as usize {
+            fail!(
+                ErrorKind::Crypto,
+                "threshold k of n multisignature: {} < {}",
+                public_keys.len(),
+                threshold
+            );
+        }
+
+        Ok(LegacyAminoPubKey {
+            threshold,
+            public_keys,
+        })
+    }
+}
+
+impl PubKey {
+    /// secp256k1 public key from its compressed or uncompressed SEC1 encoding
+    pub fn secp256k1(bytes: &[u8]) -> Result<Self, Error> {
+        let key = ecdsa::VerifyingKey::from_sec1_bytes(bytes)
+            .map_err(|e| ErrorKind::Crypto.context(e))?;
+        Ok(PubKey::Secp256k1(key))
+    }
+
+    /// ed25519 public key from its 32 bytes
+    pub fn ed25519(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = <[u8; 32]>::try_from(bytes)
+            .map_err(|_| format_err!(ErrorKind::Crypto, "invalid ed25519 public key length"))?;
+        let key = ed25519_dalek::VerifyingKey::from_bytes(&bytes)
+            .map_err(|e| ErrorKind::Crypto.context(e))?;
+        Ok(PubKey::Ed25519(key))
+    }
+
+    /// Ethermint secp256k1 public key from its SEC1 encoding
+    pub fn eth_secp256k1(bytes: &[u8]) -> Result<Self, Error> {
+        let key = ecdsa::VerifyingKey::from_sec1_bytes(bytes)
+            .map_err(|e| ErrorKind::Crypto.context(e))?;
+        Ok(PubKey::EthSecp256k1(key))
+    }
+
+    /// Single key of type `type_url` from its raw bytes
+    fn from_bytes(type_url: &str, bytes: &[u8]) -> Result<Self, Error> {
+        match type_url {
+            SECP256K1_PUB_KEY_TYPE_URL => PubKey::secp256k1(bytes),
+            ED25519_PUB_KEY_TYPE_URL => PubKey::ed25519(bytes),
+            ETH_SECP256K1_PUB_KEY_TYPE_URL => PubKey::eth_secp256k1(bytes),
+            type_url => Err(format_err!(
+                ErrorKind::Crypto,
+                "unsupported public key type {}",
+                type_url
+            )
+            .into()),
+        }
+    }
+
+    /// Signing algorithm of single keys, `None` for multisig keys
+    pub fn algo(&self) -> Option<SigningAlgo> {
+        match self {
+            PubKey::Secp256k1(_) => Some(SigningAlgo::Secp256k1),
+            PubKey::Ed25519(_) => Some(SigningAlgo::Ed25519),
+            PubKey::EthSecp256k1(_) => Some(SigningAlgo::EthSecp256k1),
+            PubKey::Multisig(_) => None,
+        }
+    }
+
+    /// Protobuf type URL
+    pub fn type_url(&self) -> &'static str {
+        match self {
+            PubKey::Secp256k1(_) => SECP256K1_PUB_KEY_TYPE_URL,
+            PubKey::Ed25519(_) => ED25519_PUB_KEY_TYPE_URL,
+            PubKey::EthSecp256k1(_) => ETH_SECP256K1_PUB_KEY_TYPE_URL,
+            PubKey::Multisig(_) => MULTISIG_PUB_KEY_TYPE_URL,
+        }
+    }
+
+    /// Raw bytes of single keys (compressed SEC1 point for secp256k1), or
+    /// the Amino encoding for multisig keys
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            PubKey::Secp256k1(key) | PubKey::EthSecp256k1(key) => key.to_sec1_bytes().to_vec(),
+            PubKey::Ed25519(key) => key.to_bytes().to_vec(),
+            PubKey::Multisig(_) => self.to_amino(),
+        }
+    }
+
+    /// Account address of the key
+    pub fn address(&self) -> Vec<u8> {
+        match self {
+            PubKey::Secp256k1(_) => Ripemd160::digest(Sha256::digest(self.to_bytes())).to_vec(),
+            PubKey::Ed25519(_) => Sha256::digest(self.to_bytes())[..ADDRESS_LEN].to_vec(),
+            PubKey::EthSecp256k1(key) => {
+                // Ethereum address: last 20 bytes of the hash of the
+                // uncompressed point, without its 0x04 tag
+                let point = key.to_encoded_point(false);
+                Keccak256::digest(&point.as_bytes()[1..])[32 - ADDRESS_LEN..].to_vec()
+            }
+            PubKey::Multisig(_) => Sha256::digest(self.to_amino())[..ADDRESS_LEN].to_vec(),
+        }
+    }
+
+    /// Pack into a protobuf `Any`
+    pub fn to_any(&self) -> Any {
+        let value = match self {
+            PubKey::Secp256k1(_) | PubKey::Ed25519(_) | PubKey::EthSecp256k1(_) => RawKey {
+                key: self.to_bytes(),
+            }
+            .encode_to_vec(),
+            PubKey::Multisig(multisig) => RawLegacyAminoPubKey {
+                threshold: multisig.threshold,
+                public_keys: multisig.public_keys.iter().map(PubKey::to_any).collect(),
+            }
+            .encode_to_vec(),
+        };
+
+        Any {
+            type_url: self.type_url().to_owned(),
+            value,
+        }
+    }
+
+    /// Unpack from a protobuf `Any`
+    pub fn from_any(any: &Any) -> Result<Self, Error> {
+        match any.type_url.as_str() {
+            MULTISIG_PUB_KEY_TYPE_URL => {
+                let raw = RawLegacyAminoPubKey::decode(any.value.as_slice())
+                    .map_err(|e| ErrorKind::Crypto.context(e))?;
+                Ok(PubKey::Multisig(LegacyAminoPubKey {
+                    threshold: raw.threshold,
+                    public_keys: raw
+                        .public_keys
+                        .iter()
+                        .map(PubKey::from_any)
+                        .collect::<Result<_, _>>()?,
+                }))
+            }
+            type_url => PubKey::from_bytes(type_url, &RawKey::from_any(any)?.key),
+        }
+    }
+
+    /// Legacy Amino binary encoding
+    pub fn to_amino(&self) -> Vec<u8> {
+        match self {
+            PubKey::Secp256k1(_) => amino::encode_bytes(amino::PUB_KEY_SECP256K1, &self.to_bytes()),
+            PubKey::Ed25519(_) => amino::encode_bytes(amino::PUB_KEY_ED25519, &self.to_bytes()),
+            PubKey::EthSecp256k1(_) => {
+                amino::encode_bytes(amino::PUB_KEY_ETH_SECP256K1, &self.to_bytes())
+            }
+            PubKey::Multisig(multisig) => {
+                let mut encoded = amino::prefix(amino::PUB_KEY_MULTISIG_THRESHOLD).to_vec();
+                prost::encoding::uint32::encode(1, &multisig.threshold, &mut encoded);
+                for key in &multisig.public_keys {
+                    prost::encoding::bytes::encode(2, &key.to_amino(), &mut encoded);
+                }
+                encoded
+            }
+        }
+    }
+
+    /// Decode the legacy Amino binary encoding
+    pub fn from_amino(encoded: &[u8]) -> Result<Self, Error> {
+        if encoded.starts_with(&amino::prefix(amino::PUB_KEY_MULTISIG_THRESHOLD)) {
+            let raw = RawAminoMultisig::decode(&encoded[4..])
+                .map_err(|e| ErrorKind::Crypto.context(e))?;
+            let public_keys = raw
+                .public_keys
+                .iter()
+                .map(|key| PubKey::from_amino(key))
+                .collect::<Result<_, _>>()?;
+            return Ok(PubKey::Multisig(LegacyAminoPubKey::new(
+                raw.threshold,
+                public_keys,
+            )?));
+        }
+
+        let names = [
+            amino::PUB_KEY_SECP256K1,
+            amino::PUB_KEY_ED25519,
+            amino::PUB_KEY_ETH_SECP256K1,
+        ];
+        match amino::decode_bytes(encoded, &names)? {
+            (amino::PUB_KEY_SECP256K1, bytes) => PubKey::secp256k1(&bytes),
+            (amino::PUB_KEY_ED25519, bytes) => PubKey::ed25519(&bytes),
+            (_, bytes) => PubKey::eth_secp256k1(&bytes),
+        }
+    }
+
+    /// JSON representation used by the SDK, e.g.
+    /// `{"@type":"/cosmos.crypto.secp256k1.PubKey","key":"..."}`
+    pub fn to_json(&self) -> Value {
+        match self {
+            PubKey::Secp256k1(_) | PubKey::Ed25519(_) | PubKey::EthSecp256k1(_) => json!({
+                "@type": self.type_url(),
+                "key": STANDARD.encode(self.to_bytes()),
+            }),
+            PubKey::Multisig(multisig) => json!({
+                "@type": self.type_url(),
+                "threshold": multisig.threshold,
+                "public_keys": multisig.public_keys.iter().map(PubKey::to_json).collect::<Vec<_>>(),
+            }),
+        }
+    }
+
+    /// Parse the JSON representation produced by [`PubKey::to_json`]
+    pub fn from_json(json: &Value) -> Result<Self, Error> {
+        let type_url = json["@type"]
+            .as_str()
+            .ok_or_else(|| format_err!(ErrorKind::Crypto, "public key JSON is missing @type"))?;
+
+        match type_url {
+            MULTISIG_PUB_KEY_TYPE_URL => {
+                let threshold = json["threshold"]
+                    .as_u64()
+                    .and_then(|threshold| u32::try_from(threshold).ok())
+                    .ok_or_else(|| format_err!(ErrorKind::Crypto, "invalid multisig threshold"))?;
+                let public_keys = json["public_keys"]
+                    .as_array()
+                    .ok_or_else(|| {
+                        format_err!(ErrorKind::Crypto, "multisig JSON is missing public_keys")
+                    })?
+                    .iter()
+                    .map(PubKey::from_json)
+                    .collect::<Result<_, _>>()?;
+
+                Ok(PubKey::Multisig(LegacyAminoPubKey::new(
+                    threshold,
+                    public_keys,
+                )?))
+            }
+            type_url => {
+                let key = json["key"].as_str().ok_or_else(|| {
+                    format_err!(ErrorKind::Crypto, "public key JSON is missing key")
+                })?;
+                let bytes = STANDARD
+                    .decode(key)
+                    .map_err(|e| ErrorKind::Crypto.context(e))?;
+
+                PubKey::from_bytes(type_url, &bytes)
+            }
+        }
+    }
+}
+
+/// Private key
+#[derive(Clone)]
+pub enum PrivKey {
+    /// secp256k1 private key
+    Secp256k1(ecdsa::SigningKey),
+    /// ed25519 private key, boxed as it carries its expanded public key
+    Ed25519(Box<ed25519_dalek::SigningKey>),
+    /// Ethermint secp256k1 private key
+    EthSecp256k1(ecdsa::SigningKey),
+}
+
+impl PrivKey {
+    /// secp256k1 private key from its 32-byte scalar
+    pub fn secp256k1(bytes: &[u8]) -> Result<Self, Error> {
+        let key = ecdsa::SigningKey::from_slice(bytes).map_err(|e| ErrorKind::Crypto.context(e))?;
+        Ok(PrivKey::Secp256k1(key))
+    }
+
+    /// ed25519 private key from its 32-byte seed, or the 64-byte seed and
+    /// public key concatenation used by Tendermint
+    pub fn ed25519(bytes: &[u8]) -> Result<Self, Error> {
+        let key = match bytes.len() {
+            32 => ed25519_dalek::SigningKey::from_bytes(
+                &<[u8; 32]>::try_from(bytes).expect("length checked"),
+            ),
+            64 => ed25519_dalek::SigningKey::from_keypair_bytes(
+                &<[u8; 64]>::try_from(bytes).expect("length checked"),
+            )
+            .map_err(|e| ErrorKind::Crypto.context(e))?,
+            len => fail!(
+                ErrorKind::Crypto,
+                "invalid ed25519 private key length {}",
+                len
+            ),
+        };
+        Ok(PrivKey::Ed25519(Box::new(key)))
+    }
+
+    /// Ethermint secp256k1 private key from its 32-byte scalar
+    pub fn eth_secp256k1(bytes: &[u8]) -> Result<Self, Error> {
+        let key = ecdsa::SigningKey::from_slice(bytes).map_err(|e| ErrorKind::Crypto.context(e))?;
+        Ok(PrivKey::EthSecp256k1(key))
+    }
+
+    /// Private key of the given algorithm from its raw bytes
+    pub fn from_bytes(algo: SigningAlgo, bytes: &[u8]) -> Result<Self, Error> {
+        match algo {
+            SigningAlgo::Secp256k1 => PrivKey::secp256k1(bytes),
+            SigningAlgo::Ed25519 => PrivKey::ed25519(bytes),
+            SigningAlgo::EthSecp256k1 => PrivKey::eth_secp256k1(bytes),
+        }
+    }
+
+    /// Signing algorithm of the key
+    pub fn signing_algo(&self) -> SigningAlgo {
+        match self {
+            PrivKey::Secp256k1(_) => SigningAlgo::Secp256k1,
+            PrivKey::Ed25519(_) => SigningAlgo::Ed25519,
+            PrivKey::EthSecp256k1(_) => SigningAlgo::EthSecp256k1,
+        }
+    }
+
+    /// Name of the signing algorithm, as used in armor headers
+    pub fn algo(&self) -> &'static str {
+        self.signing_algo().name()
+    }
+
+    /// Protobuf type URL
+    pub fn type_url(&self) -> &'static str {
+        match self {
+            PrivKey::Secp256k1(_) => SECP256K1_PRIV_KEY_TYPE_URL,
+            PrivKey::Ed25519(_) => ED25519_PRIV_KEY_TYPE_URL,
+            PrivKey::EthSecp256k1(_) => ETH_SECP256K1_PRIV_KEY_TYPE_URL,
+        }
+    }
+
+    /// Raw bytes of the key; ed25519 keys are followed by their public key
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            PrivKey::Secp256k1(key) | PrivKey::EthSecp256k1(key) => key.to_bytes().to_vec(),
+            PrivKey::Ed25519(key) => key.to_keypair_bytes().to_vec(),
+        }
+    }
+
+    /// Public key of this private key
+    pub fn pub_key(&self) -> PubKey {
+        match self {
+            PrivKey::Secp256k1(key) => PubKey::Secp256k1(*key.verifying_key()),
+            PrivKey::Ed25519(key) => PubKey::Ed25519(key.verifying_key()),
+            PrivKey::EthSecp256k1(key) => PubKey::EthSecp256k1(*key.verifying_key()),
+        }
+    }
+
+    /// Pack into a protobuf `Any`
+    pub fn to_any(&self) -> Any {
+        Any {
+            type_url: self.type_url().to_owned(),
+            value: RawKey {
+                key: self.to_bytes(),
+            }
+            .encode_to_vec(),
+        }
+    }
+
+    /// Unpack from a protobuf `Any`
+    pub fn from_any(any: &Any) -> Result<Self, Error> {
+        match any.type_url.as_str() {
+            SECP256K1_PRIV_KEY_TYPE_URL => PrivKey::secp256k1(&RawKey::from_any(any)?.key),
+            ED25519_PRIV_KEY_TYPE_URL => PrivKey::ed25519(&RawKey::from_any(any)?.key),
+            ETH_SECP256K1_PRIV_KEY_TYPE_URL => PrivKey::eth_secp256k1(&RawKey::from_any(any)?.key),
+            type_url => Err(format_err!(
+                ErrorKind::Crypto,
+                "unsupported private key type {}",
+                type_url
+            )
+            .into()),
+        }
+    }
+
+    /// Legacy Amino binary encoding
+    pub fn to_amino(&self) -> Vec<u8> {
+        let name = match self {
+            PrivKey::Secp256k1(_) => amino::PRIV_KEY_SECP256K1,
+            PrivKey::Ed25519(_) => amino::PRIV_KEY_ED25519,
+            PrivKey::EthSecp256k1(_) => amino::PRIV_KEY_ETH_SECP256K1,
+        };
+        amino::encode_bytes(name, &self.to_bytes())
+    }
+
+    /// Decode the legacy Amino binary encoding
+    pub fn from_amino(encoded: &[u8]) -> Result<Self, Error> {
+        let names = [
+            amino::PRIV_KEY_SECP256K1,
+            amino::PRIV_KEY_ED25519,
+            amino::PRIV_KEY_ETH_SECP256K1,
+        ];
+        match amino::decode_bytes(encoded, &names)? {
+            (amino::PRIV_KEY_SECP256K1, bytes) => PrivKey::secp256k1(&bytes),
+            (amino::PRIV_KEY_ED25519, bytes) => PrivKey::ed25519(&bytes),
+            (_, bytes) => PrivKey::eth_secp256k1(&bytes),
+        }
+    }
+}
+
+impl fmt::Debug for PrivKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PrivKey({})", self.type_url())
+    }
+}
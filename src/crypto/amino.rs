@@ -0,0 +1,74 @@
+//! Legacy Amino binary encoding, as far as it is needed for keys.
+//!
+//! Registered concrete types are prefixed with 4 bytes derived from the
+//! SHA-256 hash of their registered name.
+
+use crate::error::{Error, ErrorKind};
+use abscissa_core::{fail, format_err};
+use sha2::{Digest, Sha256};
+
+/// Amino name of secp256k1 public keys
+pub const PUB_KEY_SECP256K1: &str = "tendermint/PubKeySecp256k1";
+
+/// Amino name of secp256k1 private keys
+pub const PRIV_KEY_SECP256K1: &str = "tendermint/PrivKeySecp256k1";
+
+/// Amino name of ed25519 public keys
+pub const PUB_KEY_ED25519: &str = "tendermint/PubKeyEd25519";
+
+/// Amino name of ed25519 private keys
+pub const PRIV_KEY_ED25519: &str = "tendermint/PrivKeyEd25519";
+
+/// Amino name of Ethermint secp256k1 public keys
+pub const PUB_KEY_ETH_SECP256K1: &str = "ethermint/PubKeyEthSecp256k1";
+
+/// Amino name of Ethermint secp256k1 private keys
+pub const PRIV_KEY_ETH_SECP256K1: &str = "ethermint/PrivKeyEthSecp256k1";
+
+/// Amino name of threshold multisig public keys
+pub const PUB_KEY_MULTISIG_THRESHOLD: &str = "tendermint/PubKeyMultisigThreshold";
+
+/// Prefix bytes of the concrete type registered under `name`
+pub fn prefix(name: &str) -> [u8; 4] {
+    let hash = Sha256::digest(name.as_bytes());
+    let mut bytes = hash
+        .iter()
+        .skip_while(|b| **b == 0)
+        .skip(3) // disambiguation bytes
+        .skip_while(|b| **b == 0);
+
+    let mut prefix = [0u8; 4];
+    for byte in prefix.iter_mut() {
+        *byte = *bytes.next().expect("hash too short for amino prefix");
+    }
+    prefix
+}
+
+/// Encode a registered byte array type: prefix followed by the
+/// length-prefixed bytes
+pub fn encode_bytes(name: &str, bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = prefix(name).to_vec();
+    prost::encoding::encode_varint(bytes.len() as u64, &mut encoded);
+    encoded.extend_from_slice(bytes);
+    encoded
+}
+
+/// Decode a registered byte array type, returning which of `names` it was
+/// registered under along with its bytes
+pub fn decode_bytes(
+    encoded: &[u8],
+    names: &[&'static str],
+) -> Result<(&'static str, Vec<u8>), Error> {
+    let name = names
+        .iter()
+        .find(|name| encoded.starts_with(&prefix(name)))
+        .ok_or_else(|| format_err!(ErrorKind::Crypto, "amino: unrecognized type prefix"))?;
+
+    let mut buf = &encoded[4..];
+    let len = prost::encoding::decode_varint(&mut buf).map_err(|e| ErrorKind::Crypto.context(e))?;
+    if buf.len() as u64 != len {
+        fail!(ErrorKind::Crypto, "amino: invalid {} length", name);
+    }
+
+    Ok((name, buf.to_vec()))
+}
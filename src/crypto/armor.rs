@@ -0,0 +1,229 @@
+//! Tendermint ASCII armor, as produced by `keys export` of Cosmos SDK
+//! binaries.
+//!
+//! Private keys are Amino-encoded, encrypted with xsalsa20poly1305 under the
+//! SHA-256 of a bcrypt hash of the passphrase, and wrapped in an
+//! OpenPGP-style armor block carrying the `kdf`, `salt` and key `type`
+//! headers.
+
+use super::PrivKey;
+use crate::error::{Error, ErrorKind};
+use abscissa_core::{fail, format_err};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use crypto_secretbox::aead::{Aead, KeyInit};
+use crypto_secretbox::{Nonce, XSalsa20Poly1305};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, convert::TryFrom};
+
+/// Block type of armored private keys
+pub const BLOCK_TYPE_PRIV_KEY: &str = "TENDERMINT PRIVATE KEY";
+
+/// Header naming the key derivation function
+pub const HEADER_KDF: &str = "kdf";
+
+/// Header holding the hex-encoded bcrypt salt
+pub const HEADER_SALT: &str = "salt";
+
+/// Header naming the key algorithm
+pub const HEADER_TYPE: &str = "type";
+
+/// The only supported key derivation function
+const KDF_BCRYPT: &str = "bcrypt";
+
+/// bcrypt cost used by the SDK
+const BCRYPT_SECURITY_PARAMETER: u32 = 12;
+
+/// Length of the bcrypt salt
+const SALT_LEN: usize = 16;
+
+/// Length of the xsalsa20 nonce prepended to the ciphertext
+const NONCE_LEN: usize = 24;
+
+/// Length of base64 lines in the armor body
+const LINE_LEN: usize = 64;
+
+/// Armor headers, written in sorted order
+pub type Headers = BTreeMap<String, String>;
+
+/// Encrypt `priv_key` with `passphrase` and armor it
+pub fn encrypt_armor_priv_key(priv_key: &PrivKey, passphrase: &str) -> Result<String, Error> {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+
+    let key = derive_key(&salt, passphrase)?;
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let ciphertext = XSalsa20Poly1305::new(&key.into())
+        .encrypt(&Nonce::from(nonce), priv_key.to_amino().as_slice())
+        .map_err(|_| format_err!(ErrorKind::Crypto, "encryption failed"))?;
+
+    let mut headers = Headers::new();
+    headers.insert(HEADER_KDF.to_owned(), KDF_BCRYPT.to_owned());
+    headers.insert(HEADER_SALT.to_owned(), hex::encode_upper(salt));
+    headers.insert(HEADER_TYPE.to_owned(), priv_key.algo().to_owned());
+
+    Ok(encode_armor(
+        BLOCK_TYPE_PRIV_KEY,
+        &headers,
+        &[&nonce[..], &ciphertext].concat(),
+    ))
+}
+
+/// Decrypt an armored private key with `passphrase`
+pub fn unarmor_decrypt_priv_key(armor: &str, passphrase: &str) -> Result<PrivKey, Error> {
+    let (block_type, headers, data) = decode_armor(armor)?;
+
+    if block_type != BLOCK_TYPE_PRIV_KEY {
+        fail!(
+            ErrorKind::Crypto,
+            "unrecognized armor type {:?}, expected: {:?}",
+            block_type,
+            BLOCK_TYPE_PRIV_KEY
+        );
+    }
+
+    match headers.get(HEADER_KDF).map(String::as_str) {
+        Some(KDF_BCRYPT) => (),
+        kdf => fail!(ErrorKind::Crypto, "unrecognized KDF type: {:?}", kdf),
+    }
+
+    let salt = headers
+        .get(HEADER_SALT)
+        .ok_or_else(|| format_err!(ErrorKind::Crypto, "missing salt bytes"))?;
+    let salt = hex::decode(salt)
+        .ok()
+        .and_then(|salt| <[u8; SALT_LEN]>::try_from(salt).ok())
+        .ok_or_else(|| format_err!(ErrorKind::Crypto, "error decoding salt: {}", salt))?;
+
+    if data.len() < NONCE_LEN {
+        fail!(ErrorKind::Crypto, "ciphertext too short");
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    let nonce = <[u8; NONCE_LEN]>::try_from(nonce).expect("length checked");
+
+    let key = derive_key(&salt, passphrase)?;
+    let plaintext = XSalsa20Poly1305::new(&key.into())
+        .decrypt(&Nonce::from(nonce), ciphertext)
+        .map_err(|_| format_err!(ErrorKind::Crypto, "invalid account password"))?;
+
+    let priv_key = PrivKey::from_amino(&plaintext)?;
+
+    if let Some(algo) = headers.get(HEADER_TYPE) {
+        if algo != priv_key.algo() {
+            fail!(
+                ErrorKind::Crypto,
+                "armor type {} does not match {} key",
+                algo,
+                priv_key.algo()
+            );
+        }
+    }
+
+    Ok(priv_key)
+}
+
+/// Encryption key derived from the passphrase: `SHA256(bcrypt(salt, passphrase))`
+fn derive_key(salt: &[u8; SALT_LEN], passphrase: &str) -> Result<[u8; 32], Error> {
+    let hash = bcrypt::hash_with_salt(passphrase, BCRYPT_SECURITY_PARAMETER, *salt)
+        .map_err(|e| ErrorKind::Crypto.context(e))?
+        .format_for_version(bcrypt::Version::TwoA);
+
+    Ok(Sha256::digest(hash.as_bytes()).into())
+}
+
+/// Armor `data` as a block of the given type
+pub fn encode_armor(block_type: &str, headers: &Headers, data: &[u8]) -> String {
+    let mut armor = format!("-----BEGIN {}-----\n", block_type);
+    for (key, value) in headers {
+        armor.push_str(&format!("{}: {}\n", key, value));
+    }
+    armor.push('\n');
+
+    let body = STANDARD.encode(data);
+    for line in body.as_bytes().chunks(LINE_LEN) {
+        armor.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+        armor.push('\n');
+    }
+
+    let crc = crc24(data).to_be_bytes();
+    armor.push_str(&format!("={}\n", STANDARD.encode(&crc[1..])));
+    armor.push_str(&format!("-----END {}-----", block_type));
+    armor
+}
+
+/// Decode an armored block into its type, headers and data
+pub fn decode_armor(armor: &str) -> Result<(String, Headers, Vec<u8>), Error> {
+    let mut lines = armor
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| !line.starts_with("-----BEGIN "));
+
+    let block_type = lines
+        .next()
+        .and_then(|line| line.strip_prefix("-----BEGIN "))
+        .and_then(|line| line.strip_suffix("-----"))
+        .ok_or_else(|| format_err!(ErrorKind::Crypto, "armor: missing begin line"))?
+        .to_owned();
+
+    let mut headers = Headers::new();
+    for line in lines.by_ref() {
+        if line.is_empty() {
+            break;
+        }
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| format_err!(ErrorKind::Crypto, "armor: invalid header {}", line))?;
+        headers.insert(key.trim().to_owned(), value.trim().to_owned());
+    }
+
+    let end = format!("-----END {}-----", block_type);
+    let mut body = String::new();
+    let mut checksum = None;
+    let mut ended = false;
+    for line in lines {
+        if line == end {
+            ended = true;
+            break;
+        }
+        match line.strip_prefix('=') {
+            Some(crc) => checksum = Some(crc.to_owned()),
+            None => body.push_str(line),
+        }
+    }
+    if !ended {
+        return Err(format_err!(ErrorKind::Crypto, "armor: missing end line").into());
+    }
+
+    let data = STANDARD
+        .decode(body)
+        .map_err(|e| format_err!(ErrorKind::Crypto, "armor: {}", e))?;
+
+    if let Some(checksum) = checksum {
+        let crc = crc24(&data).to_be_bytes();
+        if checksum != STANDARD.encode(&crc[1..]) {
+            return Err(format_err!(ErrorKind::Crypto, "armor: checksum mismatch").into());
+        }
+    }
+
+    Ok((block_type, headers, data))
+}
+
+/// CRC-24 checksum of OpenPGP armor (RFC 4880, section 6.1)
+fn crc24(data: &[u8]) -> u32 {
+    const INIT: u32 = 0xb7_04ce;
+    const POLY: u32 = 0x186_4cfb;
+
+    let mut crc = INIT;
+    for byte in data {
+        crc ^= (*byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x100_0000 != 0 {
+                crc ^= POLY;
+            }
+        }
+    }
+    crc & 0xff_ffff
+}
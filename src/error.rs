@@ -18,6 +18,26 @@ pub enum ErrorKind {
     /// Input/output error
     #[error("I/O error")]
     Io,
+
+    /// Key derivation or other cryptographic error
+    #[error("crypto error")]
+    Crypto,
+
+    /// Error reading from or writing to the keyring
+    #[error("keyring error")]
+    Keyring,
+
+    /// Malformed or mismatching address
+    #[error("address error")]
+    Address,
+
+    /// Malformed or out of range HD derivation path
+    #[error("hd path error")]
+    HdPath,
+
+    /// Error communicating with a Ledger device
+    #[error("ledger error")]
+    Ledger,
 }
 
 impl ErrorKind {
@@ -0,0 +1,112 @@
+//! Hierarchical deterministic key derivation
+//!
+//! BIP39 mnemonic generation and BIP32/BIP44 derivation of secp256k1 keys,
+//! following the conventions of the Cosmos SDK `crypto/hd` package. ed25519
+//! keys are derived as per SLIP-0010.
+
+mod path;
+
+pub use self::path::{ChildIndex, HdPath, BIP44_PURPOSE};
+
+use crate::crypto::{PrivKey, SigningAlgo};
+use crate::error::{Error, ErrorKind};
+use abscissa_core::format_err;
+use bip32::XPrv;
+use bip39::{Language, Mnemonic, MnemonicType, Seed};
+use hmac::{Hmac, Mac};
+use k256::ecdsa::SigningKey;
+use sha2::Sha512;
+
+/// Coin type of the Cosmos Hub as registered in SLIP-0044
+pub const DEFAULT_COIN_TYPE: u32 = 118;
+
+/// Coin type of Ether, used by Ethermint-based chains for `eth_secp256k1` keys
+pub const ETH_COIN_TYPE: u32 = 60;
+
+/// Generate a fresh 24-word English mnemonic (256 bits of entropy)
+pub fn new_mnemonic() -> Mnemonic {
+    Mnemonic::new(MnemonicType::Words24, Language::English)
+}
+
+/// Mnemonic encoding the given entropy, which must be 16 to 32 bytes long
+/// in 4-byte steps
+pub fn mnemonic_from_entropy(entropy: &[u8]) -> Result<Mnemonic, Error> {
+    Mnemonic::from_entropy(entropy, Language::English)
+        .map_err(|e| format_err!(ErrorKind::Crypto, "invalid entropy: {}", e).into())
+}
+
+/// Parse an English mnemonic, checking its words and checksum
+pub fn parse_mnemonic(phrase: &str) -> Result<Mnemonic, Error> {
+    let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    Mnemonic::from_phrase(&phrase, Language::English)
+        .map_err(|e| format_err!(ErrorKind::Crypto, "invalid mnemonic: {}", e).into())
+}
+
+/// Derive a private key of the given algorithm from a mnemonic, an optional
+/// BIP39 passphrase and an HD path.
+pub fn derive(
+    algo: SigningAlgo,
+    mnemonic: &Mnemonic,
+    bip39_passphrase: &str,
+    hd_path: &HdPath,
+) -> Result<PrivKey, Error> {
+    match algo {
+        SigningAlgo::Secp256k1 => Ok(PrivKey::Secp256k1(derive_secp256k1(
+            mnemonic,
+            bip39_passphrase,
+            hd_path,
+        )?)),
+        SigningAlgo::EthSecp256k1 => Ok(PrivKey::EthSecp256k1(derive_secp256k1(
+            mnemonic,
+            bip39_passphrase,
+            hd_path,
+        )?)),
+        SigningAlgo::Ed25519 => {
+            let seed = Seed::new(mnemonic, bip39_passphrase);
+            PrivKey::ed25519(&derive_ed25519(seed.as_bytes(), hd_path)?)
+        }
+    }
+}
+
+/// Derive a secp256k1 signing key from a mnemonic, an optional BIP39
+/// passphrase and an HD path.
+pub fn derive_secp256k1(
+    mnemonic: &Mnemonic,
+    bip39_passphrase: &str,
+    hd_path: &HdPath,
+) -> Result<SigningKey, Error> {
+    let seed = Seed::new(mnemonic, bip39_passphrase);
+    let xprv = XPrv::derive_from_path(seed.as_bytes(), &hd_path.to_derivation_path())
+        .map_err(|e| ErrorKind::Crypto.context(e))?;
+
+    Ok(xprv.private_key().clone())
+}
+
+/// Derive the 32-byte secret of an ed25519 key from a BIP39 seed and an HD
+/// path. SLIP-0010 only defines hardened derivation for ed25519, so every
+/// component of the path must be hardened.
+pub fn derive_ed25519(seed: &[u8], hd_path: &HdPath) -> Result<[u8; 32], Error> {
+    hd_path.check_hardened()?;
+
+    let (mut key, mut chain_code) = slip10_hmac(b"ed25519 seed", &[seed]);
+    for child in hd_path.components() {
+        (key, chain_code) = slip10_hmac(&chain_code, &[&[0], &key, &child.raw().to_be_bytes()]);
+    }
+
+    Ok(key)
+}
+
+/// HMAC-SHA512 of the concatenated `data`, split into key and chain code
+fn slip10_hmac(key: &[u8], data: &[&[u8]]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts any key length");
+    for chunk in data {
+        mac.update(chunk);
+    }
+    let output = mac.finalize().into_bytes();
+
+    let mut left = [0u8; 32];
+    let mut right = [0u8; 32];
+    left.copy_from_slice(&output[..32]);
+    right.copy_from_slice(&output[32..]);
+    (left, right)
+}
@@ -0,0 +1,237 @@
+//! BIP32 derivation paths.
+//!
+//! Paths are written `m/44'/118'/0'/0/0`; hardened components are marked
+//! with `'`, or with `h`/`H` as accepted by BIP32 tooling.
+
+use crate::error::{Error, ErrorKind};
+use crate::keyring::Bip44Params;
+use abscissa_core::{fail, format_err};
+use bip32::{ChildNumber, DerivationPath};
+use std::{fmt, str::FromStr};
+
+/// Purpose of BIP44 paths
+pub const BIP44_PURPOSE: u32 = 44;
+
+/// Child indices must fit in 31 bits, the top bit marking hardened ones
+const HARDENED_FLAG: u32 = 1 << 31;
+
+/// Component of a derivation path
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ChildIndex {
+    index: u32,
+    hardened: bool,
+}
+
+impl ChildIndex {
+    /// Child `index`, which must be less than or equal to 2147483647
+    pub fn new(index: u32, hardened: bool) -> Result<Self, Error> {
+        if index >= HARDENED_FLAG {
+            fail!(
+                ErrorKind::HdPath,
+                "child index {} out of range (max {})",
+                index,
+                HARDENED_FLAG - 1
+            );
+        }
+        Ok(ChildIndex { index, hardened })
+    }
+
+    /// Index of the child, without the hardened flag
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Whether the child is hardened
+    pub fn is_hardened(&self) -> bool {
+        self.hardened
+    }
+
+    /// Index with the hardened flag set for hardened children, as used in
+    /// derivation
+    pub fn raw(&self) -> u32 {
+        match self.hardened {
+            true => self.index | HARDENED_FLAG,
+            false => self.index,
+        }
+    }
+}
+
+impl fmt::Display for ChildIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.index)?;
+        if self.hardened {
+            f.write_str("'")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ChildIndex {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let (digits, hardened) = match s.strip_suffix(&['\'', 'h', 'H'][..]) {
+            Some(digits) => (digits, true),
+            None => (s, false),
+        };
+
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            fail!(ErrorKind::HdPath, "invalid path component {:?}", s);
+        }
+        let index = digits
+            .parse()
+            .map_err(|_| format_err!(ErrorKind::HdPath, "child index {} out of range", digits))?;
+
+        ChildIndex::new(index, hardened)
+    }
+}
+
+/// BIP32 derivation path
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct HdPath(Vec<ChildIndex>);
+
+impl HdPath {
+    /// BIP44 path `m/44'/{coin_type}'/{account}'/0/{index}`
+    pub fn bip44(coin_type: u32, account: u32, index: u32) -> Result<Self, Error> {
+        Ok(HdPath(vec![
+            ChildIndex::new(BIP44_PURPOSE, true)?,
+            ChildIndex::new(coin_type, true)?,
+            ChildIndex::new(account, true)?,
+            ChildIndex::new(0, false)?,
+            ChildIndex::new(index, false)?,
+        ]))
+    }
+
+    /// Components of the path, from the master key down
+    pub fn components(&self) -> &[ChildIndex] {
+        &self.0
+    }
+
+    /// Same path with every component hardened
+    pub fn hardened(&self) -> Self {
+        HdPath(
+            self.0
+                .iter()
+                .map(|child| ChildIndex {
+                    hardened: true,
+                    ..*child
+                })
+                .collect(),
+        )
+    }
+
+    /// Fail unless every component of the path is hardened
+    pub fn check_hardened(&self) -> Result<(), Error> {
+        if let Some(child) = self.0.iter().find(|child| !child.hardened) {
+            fail!(
+                ErrorKind::HdPath,
+                "{}: component {} must be hardened",
+                self,
+                child
+            );
+        }
+        Ok(())
+    }
+
+    /// BIP44 parameters of the path, which must be of the form
+    /// `m/44'/coin_type'/account'/change/address_index`
+    pub fn bip44_params(&self) -> Result<Bip44Params, Error> {
+        let (purpose, coin_type, account, change, index) = match self.0.as_slice() {
+            [purpose, coin_type, account, change, index] => {
+                (purpose, coin_type, account, change, index)
+            }
+            _ => fail!(ErrorKind::HdPath, "{}: BIP44 paths have 5 components", self),
+        };
+
+        if purpose.index != BIP44_PURPOSE {
+            fail!(ErrorKind::HdPath, "{}: purpose must be 44", self);
+        }
+        if !(purpose.hardened && coin_type.hardened && account.hardened) {
+            fail!(
+                ErrorKind::HdPath,
+                "{}: purpose, coin type and account must be hardened",
+                self
+            );
+        }
+        if change.hardened || index.hardened {
+            fail!(
+                ErrorKind::HdPath,
+                "{}: change and address index must not be hardened",
+                self
+            );
+        }
+        if change.index > 1 {
+            fail!(ErrorKind::HdPath, "{}: change must be 0 or 1", self);
+        }
+
+        Ok(Bip44Params {
+            purpose: purpose.index,
+            coin_type: coin_type.index,
+            account: account.index,
+            change: change.index == 1,
+            address_index: index.index,
+        })
+    }
+
+    /// Path in the form expected by the `bip32` crate
+    pub(crate) fn to_derivation_path(&self) -> DerivationPath {
+        let mut path = DerivationPath::default();
+        for child in &self.0 {
+            path.push(ChildNumber(child.raw()));
+        }
+        path
+    }
+}
+
+impl fmt::Display for HdPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("m")?;
+        for child in &self.0 {
+            write!(f, "/{}", child)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for HdPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut components = s.split('/');
+        if components.next() != Some("m") {
+            fail!(ErrorKind::HdPath, "{:?}: path must start with m", s);
+        }
+
+        components
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(HdPath)
+    }
+}
+
+impl From<&Bip44Params> for HdPath {
+    fn from(params: &Bip44Params) -> Self {
+        HdPath(vec![
+            ChildIndex {
+                index: params.purpose,
+                hardened: true,
+            },
+            ChildIndex {
+                index: params.coin_type,
+                hardened: true,
+            },
+            ChildIndex {
+                index: params.account,
+                hardened: true,
+            },
+            ChildIndex {
+                index: params.change as u32,
+                hardened: false,
+            },
+            ChildIndex {
+                index: params.address_index,
+                hardened: false,
+            },
+        ])
+    }
+}
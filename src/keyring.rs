@@ -0,0 +1,269 @@
+//! Keyring
+//!
+//! Storage layer shared by all `keys` subcommands. A [`Keyring`] backend only
+//! needs to store opaque items by key; records are laid out on top of it the
+//! same way the Cosmos SDK keyring does: each key is stored under
+//! `<name>.info`, with a secondary `<hex address>.address` entry pointing back
+//! at it.
+
+mod file;
+mod jwe;
+#[cfg(target_os = "linux")]
+mod kwallet;
+mod legacy;
+mod memory;
+mod os;
+mod pass;
+mod record;
+
+pub use self::file::{FileKeyring, PassphrasePrompt};
+#[cfg(target_os = "linux")]
+pub use self::kwallet::KWalletKeyring;
+pub use self::memory::MemoryKeyring;
+pub use self::os::OsKeyring;
+pub use self::pass::PassKeyring;
+pub use self::record::{Bip44Params, KeyItem, KeyRecord};
+
+use crate::address;
+use crate::error::{Error, ErrorKind};
+use abscissa_core::{fail, format_err};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Service name under which keys are stored in the credential store
+pub const SERVICE_NAME: &str = "amon";
+
+/// Default prefix of the entries of the `pass` backend
+pub const DEFAULT_PASS_PREFIX: &str = "keyring-amon";
+
+/// Suffix of the items holding key records
+const INFO_SUFFIX: &str = ".info";
+
+/// Suffix of the items mapping an address to the name of its key
+const ADDRESS_SUFFIX: &str = ".address";
+
+/// Keyring backends
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyringBackend {
+    /// Operating system's default credentials store
+    #[default]
+    Os,
+    /// Encrypted file-based keystore within the app's configuration directory
+    File,
+    /// KDE Wallet Manager
+    Kwallet,
+    /// The `pass` command line utility
+    Pass,
+    /// Unencrypted file-based keystore, for testing purposes only
+    Test,
+    /// In-memory keystore, discarded when the command exits
+    Memory,
+}
+
+impl fmt::Display for KeyringBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            KeyringBackend::Os => "os",
+            KeyringBackend::File => "file",
+            KeyringBackend::Kwallet => "kwallet",
+            KeyringBackend::Pass => "pass",
+            KeyringBackend::Test => "test",
+            KeyringBackend::Memory => "memory",
+        })
+    }
+}
+
+/// Key storage.
+///
+/// Backends implement the item-level methods; key records are read and
+/// written through the provided methods so that every backend shares the
+/// same layout.
+pub trait Keyring {
+    /// Read the item stored under `key`, if any
+    fn item(&self, key: &str) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Store `data` under `key`, replacing any existing item
+    fn set_item(&self, key: &str, data: &[u8]) -> Result<(), Error>;
+
+    /// Remove the item stored under `key`
+    fn remove_item(&self, key: &str) -> Result<(), Error>;
+
+    /// Keys of all stored items
+    fn item_keys(&self) -> Result<Vec<String>, Error>;
+
+    /// Check whether a key is stored under `name`
+    fn exists(&self, name: &str) -> Result<bool, Error> {
+        Ok(self.item(&info_key(name))?.is_some())
+    }
+
+    /// Fetch the key stored under `name`
+    fn get(&self, name: &str) -> Result<KeyRecord, Error> {
+        let data = self
+            .item(&info_key(name))?
+            .ok_or_else(|| format_err!(ErrorKind::Keyring, "{}: key not found", name))?;
+
+        if KeyRecord::is_legacy(&data) {
+            fail!(
+                ErrorKind::Keyring,
+                "{}: legacy amino record, run `keys migrate` first",
+                name
+            );
+        }
+        KeyRecord::decode(&data)
+    }
+
+    /// Fetch the key whose address is the bech32 `address`, whatever its
+    /// prefix, through the address index
+    fn get_by_address(&self, address: &str) -> Result<KeyRecord, Error> {
+        let (_, bytes) = address::decode(address)?;
+        let key = self
+            .item(&address_key(&bytes))?
+            .ok_or_else(|| format_err!(ErrorKind::Keyring, "{}: key not found", address))?;
+
+        let name = String::from_utf8(key)
+            .ok()
+            .and_then(|key| key.strip_suffix(INFO_SUFFIX).map(str::to_owned))
+            .ok_or_else(|| format_err!(ErrorKind::Keyring, "{}: invalid address index", address))?;
+        self.get(&name)
+    }
+
+    /// Store `record` under its name along with its address index entry
+    fn put(&self, record: &KeyRecord) -> Result<(), Error> {
+        let key = info_key(&record.name);
+
+        self.set_item(&key, &record.encode())?;
+        self.set_item(&address_key(&record.address()), key.as_bytes())
+    }
+
+    /// Remove the key stored under `name` and its address index entry
+    fn delete(&self, name: &str) -> Result<(), Error> {
+        let record = self.get(name)?;
+
+        self.remove_item(&address_key(&record.address()))?;
+        self.remove_item(&info_key(name))
+    }
+
+    /// Move the key stored under `old_name` to `new_name`, which must be
+    /// free. The new record is written before the old one is removed, so an
+    /// interrupted rename never loses the key.
+    fn rename(&self, old_name: &str, new_name: &str) -> Result<(), Error> {
+        if self.exists(new_name)? {
+            fail!(
+                ErrorKind::Keyring,
+                "rename failed, {}: key already exists",
+                new_name
+            );
+        }
+
+        let mut record = self.get(old_name)?;
+        record.name = new_name.to_owned();
+
+        self.put(&record)?;
+        self.remove_item(&info_key(old_name))
+    }
+
+    /// Rewrite the legacy Amino record stored under `name` as a protobuf
+    /// record. Returns `false` if the record was already migrated.
+    fn migrate(&self, name: &str) -> Result<bool, Error> {
+        let data = self
+            .item(&info_key(name))?
+            .ok_or_else(|| format_err!(ErrorKind::Keyring, "{}: key not found", name))?;
+
+        if !KeyRecord::is_legacy(&data) {
+            return Ok(false);
+        }
+
+        let record = KeyRecord::decode_legacy(&data)?;
+        if record.name != name {
+            fail!(
+                ErrorKind::Keyring,
+                "{}: record is named {}",
+                name,
+                record.name
+            );
+        }
+
+        self.put(&record)?;
+        Ok(true)
+    }
+
+    /// Names of all keys in the keyring, sorted
+    fn names(&self) -> Result<Vec<String>, Error> {
+        let mut names = self
+            .item_keys()?
+            .iter()
+            .filter_map(|key| key.strip_suffix(INFO_SUFFIX).map(str::to_owned))
+            .collect::<Vec<_>>();
+        names.sort();
+        Ok(names)
+    }
+
+    /// All keys in the keyring, sorted by name
+    fn list(&self) -> Result<Vec<KeyRecord>, Error> {
+        self.names()?.iter().map(|name| self.get(name)).collect()
+    }
+}
+
+/// Key of the item holding the record of the key `name`
+fn info_key(name: &str) -> String {
+    format!("{}{}", name, INFO_SUFFIX)
+}
+
+/// Key of the item indexing `address`
+fn address_key(address: &[u8]) -> String {
+    format!("{}{}", hex::encode(address), ADDRESS_SUFFIX)
+}
+
+/// Item as serialized by the Go `99designs/keyring` library, which backs the
+/// Cosmos SDK keyring. Backends storing JSON use this envelope so that their
+/// entries can be shared with SDK-based binaries.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase", default)]
+struct Item {
+    key: String,
+    #[serde(
+        serialize_with = "serialize_data",
+        deserialize_with = "deserialize_data"
+    )]
+    data: Vec<u8>,
+    label: String,
+    description: String,
+    keychain_not_trust_application: bool,
+    keychain_not_synchronizable: bool,
+}
+
+impl Item {
+    fn new(key: &str, data: &[u8]) -> Self {
+        Item {
+            key: key.to_owned(),
+            data: data.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn to_json(&self) -> Result<Vec<u8>, Error> {
+        serde_json::to_vec(self).map_err(|e| ErrorKind::Keyring.context(e).into())
+    }
+
+    fn from_json(json: &[u8]) -> Result<Self, Error> {
+        serde_json::from_slice(json).map_err(|e| ErrorKind::Keyring.context(e).into())
+    }
+}
+
+/// Go encodes `[]byte` as standard base64
+fn serialize_data<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    serializer.serialize_str(&STANDARD.encode(data))
+}
+
+/// Go decodes a `null` `[]byte` as empty
+fn deserialize_data<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::de::Error as _;
+
+    match Option::<String>::deserialize(deserializer)? {
+        Some(data) => STANDARD.decode(data).map_err(D::Error::custom),
+        None => Ok(Vec::new()),
+    }
+}
@@ -0,0 +1,261 @@
+//! `file` backend: JWE-encrypted files, laid out like the Cosmos SDK
+//! `keyring-file` directory so that it can be shared with SDK binaries.
+
+use super::{jwe, Item, Keyring};
+use crate::error::{Error, ErrorKind};
+use abscissa_core::format_err;
+use rand::{rngs::OsRng, RngCore};
+use std::{
+    cell::RefCell,
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// File holding the bcrypt hash of the keyring passphrase
+const KEYHASH_FILENAME: &str = "keyhash";
+
+/// Passphrase prompts before giving up
+const MAX_PASSPHRASE_ENTRY_ATTEMPTS: u32 = 3;
+
+/// bcrypt cost of the keyhash. The SDK asks for a cost of 2, which the Go
+/// implementation silently raises to its default of 10.
+const KEYHASH_COST: u32 = 10;
+
+/// Prompt reading a passphrase from the user
+pub type PassphrasePrompt = Box<dyn Fn(&str) -> io::Result<String>>;
+
+/// Keyring storing each item in its own JWE-encrypted file.
+///
+/// The passphrase is requested on first access to an encrypted item. On
+/// first use it is confirmed and its bcrypt hash is written to `keyhash`;
+/// afterwards it is checked against that hash.
+pub struct FileKeyring {
+    dir: PathBuf,
+    password: RefCell<Option<String>>,
+    prompt: Option<PassphrasePrompt>,
+}
+
+impl FileKeyring {
+    /// Name of the directory holding the `file` backend, relative to the
+    /// keyring directory
+    pub const DIR_NAME: &'static str = "keyring-file";
+
+    /// Name of the directory holding the `test` backend, relative to the
+    /// keyring directory
+    pub const TEST_DIR_NAME: &'static str = "keyring-test";
+
+    /// Passphrase of the `test` backend, hardcoded just like in the SDK
+    pub const TEST_PASSWORD: &'static str = "test";
+
+    /// Keyring in `dir`, unlocked with a passphrase read through `prompt`
+    pub fn new(dir: impl Into<PathBuf>, prompt: PassphrasePrompt) -> Self {
+        FileKeyring {
+            dir: dir.into(),
+            password: RefCell::new(None),
+            prompt: Some(prompt),
+        }
+    }
+
+    /// Keyring in `dir` encrypted with a fixed `password`, which is neither
+    /// prompted for nor checked against `keyhash`
+    pub fn with_password(dir: impl Into<PathBuf>, password: &str) -> Self {
+        FileKeyring {
+            dir: dir.into(),
+            password: RefCell::new(Some(password.to_owned())),
+            prompt: None,
+        }
+    }
+
+    /// Keyring of the `test` backend in `dir`.
+    ///
+    /// Entries are encrypted with the well-known [`Self::TEST_PASSWORD`], so
+    /// they are effectively stored in plaintext and never prompt for a
+    /// passphrase. The directory is interchangeable with the SDK's
+    /// `keyring-test`.
+    pub fn new_test(dir: impl Into<PathBuf>) -> Self {
+        Self::with_password(dir, Self::TEST_PASSWORD)
+    }
+
+    /// Directory holding the keyring files
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn password(&self) -> Result<String, Error> {
+        if let Some(password) = self.password.borrow().as_ref() {
+            return Ok(password.clone());
+        }
+
+        let password = self.unlock()?;
+        *self.password.borrow_mut() = Some(password.clone());
+        Ok(password)
+    }
+
+    fn unlock(&self) -> Result<String, Error> {
+        let prompt = self
+            .prompt
+            .as_ref()
+            .ok_or_else(|| format_err!(ErrorKind::Keyring, "no keyring passphrase"))?;
+
+        let keyhash_path = self.dir.join(KEYHASH_FILENAME);
+        let keyhash = match fs::read_to_string(&keyhash_path) {
+            Ok(keyhash) => Some(keyhash),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        for attempt in 1..=MAX_PASSPHRASE_ENTRY_ATTEMPTS {
+            let password = prompt(&format!(
+                "Enter keyring passphrase (attempt {}/{}):",
+                attempt, MAX_PASSPHRASE_ENTRY_ATTEMPTS
+            ))?;
+
+            if let Some(keyhash) = &keyhash {
+                if bcrypt::verify(&password, keyhash.trim()).unwrap_or(false) {
+                    return Ok(password);
+                }
+
+                eprintln!("incorrect passphrase");
+                continue;
+            }
+
+            if password != prompt("Re-enter keyring passphrase:")? {
+                eprintln!("passphrase do not match");
+                continue;
+            }
+
+            let mut salt = [0u8; 16];
+            OsRng.fill_bytes(&mut salt);
+            let keyhash = bcrypt::hash_with_salt(&password, KEYHASH_COST, salt)
+                .map_err(|e| ErrorKind::Crypto.context(e))?
+                .format_for_version(bcrypt::Version::TwoA);
+
+            self.create_dir()?;
+            write_private(&keyhash_path, keyhash.as_bytes())?;
+            return Ok(password);
+        }
+
+        Err(format_err!(ErrorKind::Keyring, "too many failed passphrase attempts").into())
+    }
+
+    fn create_dir(&self) -> Result<(), Error> {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+
+        builder.create(&self.dir).map_err(Into::into)
+    }
+
+    fn filename(&self, key: &str) -> PathBuf {
+        self.dir.join(filename_escape(key))
+    }
+}
+
+impl fmt::Debug for FileKeyring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileKeyring")
+            .field("dir", &self.dir)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Keyring for FileKeyring {
+    fn item(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        let token = match fs::read_to_string(self.filename(key)) {
+            Ok(token) => token,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let json = jwe::decrypt(&token, &self.password()?)?;
+        Ok(Some(Item::from_json(&json)?.data))
+    }
+
+    fn set_item(&self, key: &str, data: &[u8]) -> Result<(), Error> {
+        let token = jwe::encrypt(&Item::new(key, data).to_json()?, &self.password()?)?;
+
+        self.create_dir()?;
+        write_private(&self.filename(key), token.as_bytes())
+    }
+
+    fn remove_item(&self, key: &str) -> Result<(), Error> {
+        match fs::remove_file(self.filename(key)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn item_keys(&self) -> Result<Vec<String>, Error> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut keys = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                continue;
+            }
+
+            let filename = entry.file_name().to_string_lossy().into_owned();
+            if filename != KEYHASH_FILENAME {
+                keys.push(filename_unescape(&filename));
+            }
+        }
+        Ok(keys)
+    }
+}
+
+/// Write `data` to `path`, readable by the owner only
+fn write_private(path: &Path, data: &[u8]) -> Result<(), Error> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    options.open(path)?.write_all(data).map_err(Into::into)
+}
+
+/// Percent-encode the characters of `key` which are unsafe in filenames
+fn filename_escape(key: &str) -> String {
+    let mut filename = String::with_capacity(key.len());
+    for byte in key.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                filename.push(byte as char)
+            }
+            _ => filename.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    filename
+}
+
+/// Reverse [`filename_escape`]
+fn filename_unescape(filename: &str) -> String {
+    let mut bytes = Vec::with_capacity(filename.len());
+    let mut rest = filename.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = match (byte, tail) {
+            (b'%', [hi, lo, ..]) => std::str::from_utf8(&[*hi, *lo])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
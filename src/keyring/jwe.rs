@@ -0,0 +1,134 @@
+//! JSON Web Encryption of file keyring entries.
+//!
+//! Only the algorithms used by the Go `99designs/keyring` file backend are
+//! supported: `PBES2-HS256+A128KW` key wrapping with `A256GCM` content
+//! encryption, in compact serialization.
+
+use crate::error::{Error, ErrorKind};
+use abscissa_core::format_err;
+use aes_gcm::{
+    aead::{Aead, Payload},
+    Aes256Gcm, KeyInit, Nonce,
+};
+use aes_kw::KekAes128;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::{rngs::OsRng, RngCore};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::Sha256;
+use std::convert::TryFrom;
+
+const ALG: &str = "PBES2-HS256+A128KW";
+const ENC: &str = "A256GCM";
+
+/// PBKDF2 iteration count used by `jose2go`
+const ITERATION_COUNT: u32 = 8192;
+
+/// Protected header fields needed to decrypt
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+    enc: String,
+    p2c: u32,
+    p2s: String,
+}
+
+/// Encrypt `plaintext` with `password` into a compact JWE token
+pub fn encrypt(plaintext: &[u8], password: &str) -> Result<String, Error> {
+    let mut salt = [0u8; 12];
+    let mut cek = [0u8; 32];
+    let mut iv = [0u8; 12];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut cek);
+    OsRng.fill_bytes(&mut iv);
+
+    let header = json!({
+        "alg": ALG,
+        "enc": ENC,
+        "p2c": ITERATION_COUNT,
+        "p2s": URL_SAFE_NO_PAD.encode(salt),
+    });
+    let header = URL_SAFE_NO_PAD.encode(header.to_string());
+
+    let encrypted_key = key_encryption_key(password, &salt, ITERATION_COUNT)
+        .wrap_vec(&cek)
+        .map_err(|e| format_err!(ErrorKind::Crypto, "{}", e))?;
+
+    let mut ciphertext = Aes256Gcm::new(&cek.into())
+        .encrypt(
+            &Nonce::from(iv),
+            Payload {
+                msg: plaintext,
+                aad: header.as_bytes(),
+            },
+        )
+        .map_err(|e| format_err!(ErrorKind::Crypto, "{}", e))?;
+    let tag = ciphertext.split_off(ciphertext.len() - 16);
+
+    Ok([
+        header,
+        URL_SAFE_NO_PAD.encode(encrypted_key),
+        URL_SAFE_NO_PAD.encode(iv),
+        URL_SAFE_NO_PAD.encode(ciphertext),
+        URL_SAFE_NO_PAD.encode(tag),
+    ]
+    .join("."))
+}
+
+/// Decrypt a compact JWE token with `password`
+pub fn decrypt(token: &str, password: &str) -> Result<Vec<u8>, Error> {
+    let parts = token.trim().split('.').collect::<Vec<_>>();
+    if parts.len() != 5 {
+        return Err(format_err!(ErrorKind::Crypto, "malformed JWE token").into());
+    }
+
+    let header: Header = serde_json::from_slice(&decode(parts[0])?)
+        .map_err(|e| format_err!(ErrorKind::Crypto, "invalid JWE header: {}", e))?;
+    if header.alg != ALG || header.enc != ENC {
+        return Err(format_err!(
+            ErrorKind::Crypto,
+            "unsupported JWE algorithm {}/{}",
+            header.alg,
+            header.enc
+        )
+        .into());
+    }
+
+    let cek = key_encryption_key(password, &decode(&header.p2s)?, header.p2c)
+        .unwrap_vec(&decode(parts[1])?)
+        .map_err(|_| format_err!(ErrorKind::Crypto, "incorrect passphrase"))?;
+    let iv = <[u8; 12]>::try_from(decode(parts[2])?.as_slice())
+        .map_err(|_| format_err!(ErrorKind::Crypto, "malformed JWE token"))?;
+
+    let mut ciphertext = decode(parts[3])?;
+    ciphertext.extend(decode(parts[4])?);
+
+    Aes256Gcm::new_from_slice(&cek)
+        .map_err(|e| format_err!(ErrorKind::Crypto, "{}", e))?
+        .decrypt(
+            &Nonce::from(iv),
+            Payload {
+                msg: &ciphertext,
+                aad: parts[0].as_bytes(),
+            },
+        )
+        .map_err(|_| format_err!(ErrorKind::Crypto, "could not decrypt JWE token").into())
+}
+
+/// Derive the PBES2 key encryption key. The salt input is prefixed with the
+/// algorithm name as per RFC 7518 section 4.8.1.1.
+fn key_encryption_key(password: &str, salt: &[u8], iterations: u32) -> KekAes128 {
+    let mut salt_value = ALG.as_bytes().to_vec();
+    salt_value.push(0);
+    salt_value.extend_from_slice(salt);
+
+    let mut kek = [0u8; 16];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), &salt_value, iterations, &mut kek);
+    KekAes128::from(kek)
+}
+
+fn decode(part: &str) -> Result<Vec<u8>, Error> {
+    URL_SAFE_NO_PAD
+        .decode(part)
+        .map_err(|e| format_err!(ErrorKind::Crypto, "malformed JWE token: {}", e).into())
+}
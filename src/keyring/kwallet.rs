@@ -0,0 +1,123 @@
+//! `kwallet` backend: KDE Wallet Manager over D-Bus
+
+use super::{Item, Keyring};
+use crate::error::{Error, ErrorKind};
+use abscissa_core::format_err;
+use serde::{de::DeserializeOwned, Serialize};
+use zbus::{blocking::Connection, zvariant::Type};
+
+const DBUS_SERVICE: &str = "org.kde.kwalletd5";
+const DBUS_PATH: &str = "/modules/kwalletd5";
+const DBUS_INTERFACE: &str = "org.kde.KWallet";
+
+/// Keyring stored in a folder of a KDE wallet, with entries laid out like
+/// the SDK `kwallet` backend.
+#[derive(Debug)]
+pub struct KWalletKeyring {
+    connection: Connection,
+    handle: i32,
+    folder: String,
+    app_id: String,
+}
+
+impl KWalletKeyring {
+    /// Wallet the Cosmos SDK stores its keys in
+    pub const DEFAULT_WALLET: &'static str = "kdewallet";
+
+    /// Open `wallet` on the session bus, identifying as `app_id`. The user
+    /// may be asked by KWallet to grant access. The folder is created by
+    /// KWallet on first write.
+    pub fn open(wallet: &str, folder: &str, app_id: &str) -> Result<Self, Error> {
+        let connection = Connection::session().map_err(|e| ErrorKind::Keyring.context(e))?;
+        let mut keyring = KWalletKeyring {
+            connection,
+            handle: -1,
+            folder: folder.to_owned(),
+            app_id: app_id.to_owned(),
+        };
+
+        keyring.handle = keyring.call("open", &(wallet, 0i64, app_id))?;
+        if keyring.handle < 0 {
+            return Err(format_err!(ErrorKind::Keyring, "could not open wallet {}", wallet).into());
+        }
+
+        Ok(keyring)
+    }
+
+    fn call<B, R>(&self, method: &str, body: &B) -> Result<R, Error>
+    where
+        B: Serialize + Type,
+        R: DeserializeOwned + Type,
+    {
+        self.connection
+            .call_method(
+                Some(DBUS_SERVICE),
+                DBUS_PATH,
+                Some(DBUS_INTERFACE),
+                method,
+                body,
+            )
+            .and_then(|reply| reply.body())
+            .map_err(|e| ErrorKind::Keyring.context(e).into())
+    }
+}
+
+impl Keyring for KWalletKeyring {
+    fn item(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        let exists: bool = self.call(
+            "hasEntry",
+            &(self.handle, self.folder.as_str(), key, self.app_id.as_str()),
+        )?;
+        if !exists {
+            return Ok(None);
+        }
+
+        let json: Vec<u8> = self.call(
+            "readEntry",
+            &(self.handle, self.folder.as_str(), key, self.app_id.as_str()),
+        )?;
+        Ok(Some(Item::from_json(&json)?.data))
+    }
+
+    fn set_item(&self, key: &str, data: &[u8]) -> Result<(), Error> {
+        let json = Item::new(key, data).to_json()?;
+        let status: i32 = self.call(
+            "writeEntry",
+            &(
+                self.handle,
+                self.folder.as_str(),
+                key,
+                json,
+                self.app_id.as_str(),
+            ),
+        )?;
+
+        if status != 0 {
+            return Err(
+                format_err!(ErrorKind::Keyring, "could not write {} to wallet", key).into(),
+            );
+        }
+        Ok(())
+    }
+
+    fn remove_item(&self, key: &str) -> Result<(), Error> {
+        let status: i32 = self.call(
+            "removeEntry",
+            &(self.handle, self.folder.as_str(), key, self.app_id.as_str()),
+        )?;
+
+        if status != 0 {
+            return Err(
+                format_err!(ErrorKind::Keyring, "could not remove {} from wallet", key).into(),
+            );
+        }
+        Ok(())
+    }
+
+    fn item_keys(&self) -> Result<Vec<String>, Error> {
+        self.call(
+            "entryList",
+            &(self.handle, self.folder.as_str(), self.app_id.as_str()),
+        )
+    }
+}
@@ -0,0 +1,151 @@
+//! Legacy Amino-encoded key records.
+//!
+//! Cosmos SDK versions before v0.46 stored keys as Amino-encoded `Info`
+//! values (`localInfo`, `ledgerInfo`, `offlineInfo` and `multiInfo`),
+//! length-prefixed and tagged with their registered type prefix. They are
+//! converted to [`KeyRecord`]s by `keys migrate`.
+
+use super::record::{Bip44Params, KeyItem, KeyRecord};
+use crate::crypto::{amino, PrivKey, PubKey};
+use crate::error::{Error, ErrorKind};
+use abscissa_core::{fail, format_err};
+use prost::Message;
+
+/// Amino name of legacy local key records
+const LOCAL_INFO: &str = "crypto/keys/localInfo";
+
+/// Amino name of legacy Ledger key records
+const LEDGER_INFO: &str = "crypto/keys/ledgerInfo";
+
+/// Amino name of legacy offline key records
+const OFFLINE_INFO: &str = "crypto/keys/offlineInfo";
+
+/// Amino name of legacy multisig key records
+const MULTI_INFO: &str = "crypto/keys/multiInfo";
+
+/// `localInfo`; the "armor" holds the Amino-encoded private key
+#[derive(Clone, PartialEq, Message)]
+struct LegacyLocalInfo {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub_key: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    priv_key_armor: Vec<u8>,
+    #[prost(string, tag = "4")]
+    algo: String,
+}
+
+/// `ledgerInfo`
+#[derive(Clone, PartialEq, Message)]
+struct LegacyLedgerInfo {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub_key: Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    path: Option<Bip44Params>,
+    #[prost(string, tag = "4")]
+    algo: String,
+}
+
+/// `offlineInfo`
+#[derive(Clone, PartialEq, Message)]
+struct LegacyOfflineInfo {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub_key: Vec<u8>,
+    #[prost(string, tag = "3")]
+    algo: String,
+}
+
+/// `multiInfo`; the participants are implied by the multisig public key
+#[derive(Clone, PartialEq, Message)]
+struct LegacyMultiInfo {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub_key: Vec<u8>,
+    #[prost(uint32, tag = "3")]
+    threshold: u32,
+}
+
+impl KeyRecord {
+    /// Check whether `bytes` hold a legacy Amino `Info`
+    pub fn is_legacy(bytes: &[u8]) -> bool {
+        legacy_info(bytes).is_some()
+    }
+
+    /// Convert a legacy Amino `Info` into a record
+    pub fn decode_legacy(bytes: &[u8]) -> Result<Self, Error> {
+        let (name, fields) = legacy_info(bytes)
+            .ok_or_else(|| format_err!(ErrorKind::Keyring, "not a legacy amino record"))?;
+        let decode_error = |e| format_err!(ErrorKind::Keyring, "invalid {}: {}", name, e);
+
+        match name {
+            LOCAL_INFO => {
+                let info = LegacyLocalInfo::decode(fields).map_err(decode_error)?;
+                let priv_key = PrivKey::from_amino(&info.priv_key_armor)?;
+                check_pub_key(&info.name, &info.pub_key, &priv_key.pub_key())?;
+                Ok(KeyRecord::new_local(&info.name, priv_key))
+            }
+            LEDGER_INFO => {
+                let info = LegacyLedgerInfo::decode(fields).map_err(decode_error)?;
+                let path = info.path.ok_or_else(|| {
+                    format_err!(ErrorKind::Keyring, "{}: missing ledger path", info.name)
+                })?;
+                let pub_key = PubKey::from_amino(&info.pub_key)?;
+                Ok(KeyRecord::new_ledger(&info.name, pub_key, path))
+            }
+            OFFLINE_INFO => {
+                let info = LegacyOfflineInfo::decode(fields).map_err(decode_error)?;
+                let pub_key = PubKey::from_amino(&info.pub_key)?;
+                Ok(KeyRecord::new_offline(&info.name, pub_key))
+            }
+            _ => {
+                let info = LegacyMultiInfo::decode(fields).map_err(decode_error)?;
+                let pub_key = PubKey::from_amino(&info.pub_key)?;
+                if !matches!(&pub_key, PubKey::Multisig(multisig) if multisig.threshold == info.threshold)
+                {
+                    fail!(
+                        ErrorKind::Keyring,
+                        "{}: inconsistent multisig key",
+                        info.name
+                    );
+                }
+                Ok(KeyRecord {
+                    name: info.name,
+                    pub_key,
+                    item: KeyItem::Multi,
+                })
+            }
+        }
+    }
+}
+
+/// Split a length-prefixed Amino `Info` into its type and struct fields
+fn legacy_info(bytes: &[u8]) -> Option<(&'static str, &[u8])> {
+    let mut buf = bytes;
+    let len = prost::encoding::decode_varint(&mut buf).ok()?;
+    if buf.len() as u64 != len {
+        return None;
+    }
+
+    [LOCAL_INFO, LEDGER_INFO, OFFLINE_INFO, MULTI_INFO]
+        .iter()
+        .find(|name| buf.starts_with(&amino::prefix(name)))
+        .map(|name| (*name, &buf[4..]))
+}
+
+/// Make sure the stored public key matches the private key
+fn check_pub_key(name: &str, encoded: &[u8], expected: &PubKey) -> Result<(), Error> {
+    if PubKey::from_amino(encoded)? != *expected {
+        fail!(
+            ErrorKind::Keyring,
+            "{}: public key does not match private key",
+            name
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! `memory` backend: items kept in memory for the lifetime of the keyring
+
+use super::Keyring;
+use crate::error::Error;
+use std::{cell::RefCell, collections::BTreeMap};
+
+/// Keyring which never touches disk, used by `keys add --dry-run` and in
+/// tests.
+#[derive(Debug, Default)]
+pub struct MemoryKeyring {
+    items: RefCell<BTreeMap<String, Vec<u8>>>,
+}
+
+impl MemoryKeyring {
+    /// Empty keyring
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Keyring for MemoryKeyring {
+    fn item(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.items.borrow().get(key).cloned())
+    }
+
+    fn set_item(&self, key: &str, data: &[u8]) -> Result<(), Error> {
+        self.items
+            .borrow_mut()
+            .insert(key.to_owned(), data.to_vec());
+        Ok(())
+    }
+
+    fn remove_item(&self, key: &str) -> Result<(), Error> {
+        self.items.borrow_mut().remove(key);
+        Ok(())
+    }
+
+    fn item_keys(&self) -> Result<Vec<String>, Error> {
+        Ok(self.items.borrow().keys().cloned().collect())
+    }
+}
@@ -0,0 +1,88 @@
+//! `os` backend: the operating system's credentials store
+
+use super::Keyring;
+use crate::error::{Error, ErrorKind};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+/// Entry listing the keys of all items, as credential stores can't be
+/// enumerated portably. Item keys always carry a suffix so they can't
+/// collide with it.
+const INDEX_KEY: &str = "index";
+
+/// Keyring stored in the operating system's credentials store (Keychain,
+/// Secret Service, Windows Credential Manager), one entry per item.
+#[derive(Clone, Debug)]
+pub struct OsKeyring {
+    service: String,
+}
+
+impl OsKeyring {
+    /// Keyring storing its entries under `service`
+    pub fn new(service: impl Into<String>) -> Self {
+        OsKeyring {
+            service: service.into(),
+        }
+    }
+
+    fn entry(&self, key: &str) -> Result<keyring::Entry, Error> {
+        keyring::Entry::new(&self.service, key).map_err(|e| ErrorKind::Keyring.context(e).into())
+    }
+
+    fn read(&self, key: &str) -> Result<Option<String>, Error> {
+        match self.entry(key)?.get_password() {
+            Ok(data) => Ok(Some(data)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(ErrorKind::Keyring.context(e).into()),
+        }
+    }
+
+    fn write_index(&self, keys: &[String]) -> Result<(), Error> {
+        let index = serde_json::to_string(keys).map_err(|e| ErrorKind::Keyring.context(e))?;
+
+        self.entry(INDEX_KEY)?
+            .set_password(&index)
+            .map_err(|e| ErrorKind::Keyring.context(e).into())
+    }
+}
+
+impl Keyring for OsKeyring {
+    fn item(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        self.read(key)?
+            .map(|data| STANDARD.decode(data))
+            .transpose()
+            .map_err(|e| ErrorKind::Keyring.context(e).into())
+    }
+
+    fn set_item(&self, key: &str, data: &[u8]) -> Result<(), Error> {
+        self.entry(key)?
+            .set_password(&STANDARD.encode(data))
+            .map_err(|e| ErrorKind::Keyring.context(e))?;
+
+        let mut keys = self.item_keys()?;
+        if !keys.iter().any(|k| k == key) {
+            keys.push(key.to_owned());
+            self.write_index(&keys)?;
+        }
+        Ok(())
+    }
+
+    fn remove_item(&self, key: &str) -> Result<(), Error> {
+        match self.entry(key)?.delete_password() {
+            Ok(()) | Err(keyring::Error::NoEntry) => (),
+            Err(e) => return Err(ErrorKind::Keyring.context(e).into()),
+        }
+
+        let mut keys = self.item_keys()?;
+        keys.retain(|k| k != key);
+        self.write_index(&keys)
+    }
+
+    fn item_keys(&self) -> Result<Vec<String>, Error> {
+        match self.read(INDEX_KEY)? {
+            Some(index) => {
+                serde_json::from_str(&index).map_err(|e| ErrorKind::Keyring.context(e).into())
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! `pass` backend: GPG-encrypted entries managed by the password-store CLI
+
+use super::{Item, Keyring};
+use crate::error::{Error, ErrorKind};
+use abscissa_core::format_err;
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// Keyring storing each item as a `pass` entry named `<prefix>/<key>`, with
+/// the same JSON envelope as the SDK `pass` backend.
+#[derive(Clone, Debug)]
+pub struct PassKeyring {
+    dir: PathBuf,
+    prefix: String,
+    gnupg_home: Option<PathBuf>,
+}
+
+impl PassKeyring {
+    /// Command invoked to manage the password store
+    pub const COMMAND: &'static str = "pass";
+
+    /// Keyring under `prefix` in the password store at `dir`. When `dir` is
+    /// `None` it is located like `pass` does, from `PASSWORD_STORE_DIR` or
+    /// `~/.password-store`.
+    pub fn new(dir: Option<PathBuf>, prefix: impl Into<String>) -> Result<Self, Error> {
+        let dir = dir
+            .or_else(|| env::var_os("PASSWORD_STORE_DIR").map(PathBuf::from))
+            .or_else(|| dirs_next::home_dir().map(|home| home.join(".password-store")))
+            .ok_or_else(|| format_err!(ErrorKind::Keyring, "could not find password store"))?;
+
+        Ok(PassKeyring {
+            dir,
+            prefix: prefix.into(),
+            gnupg_home: None,
+        })
+    }
+
+    /// Run GnuPG with the keys in `dir` rather than the default `GNUPGHOME`
+    pub fn with_gnupg_home(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.gnupg_home = Some(dir.into());
+        self
+    }
+
+    /// Directory of the password store
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_name(&self, key: &str) -> String {
+        format!("{}/{}", self.prefix, key)
+    }
+
+    fn pass(&self, args: &[&str]) -> Command {
+        let mut cmd = Command::new(Self::COMMAND);
+        cmd.args(args)
+            .env("PASSWORD_STORE_DIR", &self.dir)
+            .stderr(Stdio::inherit());
+        if let Some(gnupg_home) = &self.gnupg_home {
+            cmd.env("GNUPGHOME", gnupg_home);
+        }
+        cmd
+    }
+}
+
+impl Keyring for PassKeyring {
+    fn item(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        let path = self.dir.join(&self.prefix).join(format!("{}.gpg", key));
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let output = self.pass(&["show", &self.entry_name(key)]).output()?;
+        if !output.status.success() {
+            return Err(format_err!(ErrorKind::Keyring, "pass show {} failed", key).into());
+        }
+
+        Ok(Some(Item::from_json(&output.stdout)?.data))
+    }
+
+    fn set_item(&self, key: &str, data: &[u8]) -> Result<(), Error> {
+        let json = Item::new(key, data).to_json()?;
+        let mut child = self
+            .pass(&["insert", "-m", "-f", &self.entry_name(key)])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()?;
+
+        child
+            .stdin
+            .take()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::BrokenPipe))?
+            .write_all(&json)?;
+
+        if !child.wait()?.success() {
+            return Err(format_err!(ErrorKind::Keyring, "pass insert {} failed", key).into());
+        }
+        Ok(())
+    }
+
+    fn remove_item(&self, key: &str) -> Result<(), Error> {
+        let status = self
+            .pass(&["rm", "-f", &self.entry_name(key)])
+            .stdout(Stdio::null())
+            .status()?;
+
+        if !status.success() {
+            return Err(format_err!(ErrorKind::Keyring, "pass rm {} failed", key).into());
+        }
+        Ok(())
+    }
+
+    fn item_keys(&self) -> Result<Vec<String>, Error> {
+        let entries = match fs::read_dir(self.dir.join(&self.prefix)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut keys = Vec::new();
+        for entry in entries {
+            let filename = entry?.file_name().to_string_lossy().into_owned();
+            if let Some(key) = filename.strip_suffix(".gpg") {
+                keys.push(key.to_owned());
+            }
+        }
+        Ok(keys)
+    }
+}
@@ -0,0 +1,217 @@
+//! Key records, modeled on the Cosmos SDK `keyring.Record`.
+//!
+//! Records are stored protobuf-encoded exactly like the SDK stores them, so
+//! keyring entries can be shared with SDK-based binaries.
+
+use crate::crypto::{Any, PrivKey, PubKey, SigningAlgo};
+use crate::error::{Error, ErrorKind};
+use crate::hd::HdPath;
+use abscissa_core::{fail, format_err};
+use prost::Message;
+use std::fmt;
+
+/// A key stored in the keyring
+#[derive(Clone, Debug)]
+pub struct KeyRecord {
+    /// Name the key is stored under
+    pub name: String,
+    /// Public key
+    pub pub_key: PubKey,
+    /// Kind of key, with its type-specific data
+    pub item: KeyItem,
+}
+
+/// Kind of a stored key
+#[derive(Clone, Debug)]
+pub enum KeyItem {
+    /// Private key stored in the keyring
+    Local(PrivKey),
+    /// Private key held by a Ledger device at the given path
+    Ledger(Bip44Params),
+    /// Multisig public key
+    Multi,
+    /// Public key only
+    Offline,
+}
+
+/// BIP44 derivation path of Ledger keys, `m/purpose'/coin_type'/account'/change/address_index`
+#[derive(Clone, Copy, Eq, PartialEq, Message)]
+pub struct Bip44Params {
+    /// Purpose, always 44
+    #[prost(uint32, tag = "1")]
+    pub purpose: u32,
+    /// Registered coin type
+    #[prost(uint32, tag = "2")]
+    pub coin_type: u32,
+    /// Account index
+    #[prost(uint32, tag = "3")]
+    pub account: u32,
+    /// Whether the address is a change (internal) address
+    #[prost(bool, tag = "4")]
+    pub change: bool,
+    /// Address index
+    #[prost(uint32, tag = "5")]
+    pub address_index: u32,
+}
+
+impl Bip44Params {
+    /// BIP44 parameters of an external address
+    pub fn new(coin_type: u32, account: u32, address_index: u32) -> Self {
+        Bip44Params {
+            purpose: 44,
+            coin_type,
+            account,
+            change: false,
+            address_index,
+        }
+    }
+}
+
+impl fmt::Display for Bip44Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        HdPath::from(self).fmt(f)
+    }
+}
+
+impl KeyRecord {
+    /// Record of a private key stored in the keyring
+    pub fn new_local(name: &str, priv_key: PrivKey) -> Self {
+        KeyRecord {
+            name: name.to_owned(),
+            pub_key: priv_key.pub_key(),
+            item: KeyItem::Local(priv_key),
+        }
+    }
+
+    /// Record of a key held by a Ledger device
+    pub fn new_ledger(name: &str, pub_key: PubKey, path: Bip44Params) -> Self {
+        KeyRecord {
+            name: name.to_owned(),
+            pub_key,
+            item: KeyItem::Ledger(path),
+        }
+    }
+
+    /// Record of a multisig public key
+    pub fn new_multi(name: &str, pub_key: PubKey) -> Self {
+        KeyRecord {
+            name: name.to_owned(),
+            pub_key,
+            item: KeyItem::Multi,
+        }
+    }
+
+    /// Record of a public key only
+    pub fn new_offline(name: &str, pub_key: PubKey) -> Self {
+        KeyRecord {
+            name: name.to_owned(),
+            pub_key,
+            item: KeyItem::Offline,
+        }
+    }
+
+    /// Key type as displayed by the SDK: `local`, `ledger`, `multi` or `offline`
+    pub fn key_type(&self) -> &'static str {
+        match self.item {
+            KeyItem::Local(_) => "local",
+            KeyItem::Ledger(_) => "ledger",
+            KeyItem::Multi => "multi",
+            KeyItem::Offline => "offline",
+        }
+    }
+
+    /// Signing algorithm of the key, as recorded by the type of its public
+    /// key; `None` for multisig keys
+    pub fn algo(&self) -> Option<SigningAlgo> {
+        self.pub_key.algo()
+    }
+
+    /// Account address of the key
+    pub fn address(&self) -> Vec<u8> {
+        self.pub_key.address()
+    }
+
+    /// Protobuf encoding of the record
+    pub fn encode(&self) -> Vec<u8> {
+        let item = match &self.item {
+            KeyItem::Local(priv_key) => RawItem::Local(RawLocal {
+                priv_key: Some(priv_key.to_any()),
+            }),
+            KeyItem::Ledger(path) => RawItem::Ledger(RawLedger { path: Some(*path) }),
+            KeyItem::Multi => RawItem::Multi(RawEmpty {}),
+            KeyItem::Offline => RawItem::Offline(RawEmpty {}),
+        };
+
+        RawRecord {
+            name: self.name.clone(),
+            pub_key: Some(self.pub_key.to_any()),
+            item: Some(item),
+        }
+        .encode_to_vec()
+    }
+
+    /// Decode a protobuf-encoded record
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let raw = RawRecord::decode(bytes).map_err(|e| ErrorKind::Keyring.context(e))?;
+
+        let pub_key = raw
+            .pub_key
+            .as_ref()
+            .ok_or_else(|| format_err!(ErrorKind::Keyring, "{}: missing public key", raw.name))?;
+        let pub_key = PubKey::from_any(pub_key)?;
+
+        let item = match raw.item {
+            Some(RawItem::Local(RawLocal {
+                priv_key: Some(priv_key),
+            })) => KeyItem::Local(PrivKey::from_any(&priv_key)?),
+            Some(RawItem::Ledger(RawLedger { path: Some(path) })) => KeyItem::Ledger(path),
+            Some(RawItem::Multi(_)) => KeyItem::Multi,
+            Some(RawItem::Offline(_)) => KeyItem::Offline,
+            _ => fail!(ErrorKind::Keyring, "{}: unrecognized record type", raw.name),
+        };
+
+        Ok(KeyRecord {
+            name: raw.name,
+            pub_key,
+            item,
+        })
+    }
+}
+
+/// Protobuf message of `cosmos.crypto.keyring.v1.Record`
+#[derive(Clone, PartialEq, Message)]
+struct RawRecord {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(message, optional, tag = "2")]
+    pub_key: Option<Any>,
+    #[prost(oneof = "RawItem", tags = "3, 4, 5, 6")]
+    item: Option<RawItem>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum RawItem {
+    #[prost(message, tag = "3")]
+    Local(RawLocal),
+    #[prost(message, tag = "4")]
+    Ledger(RawLedger),
+    #[prost(message, tag = "5")]
+    Multi(RawEmpty),
+    #[prost(message, tag = "6")]
+    Offline(RawEmpty),
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct RawLocal {
+    #[prost(message, optional, tag = "1")]
+    priv_key: Option<Any>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct RawLedger {
+    #[prost(message, optional, tag = "1")]
+    path: Option<Bip44Params>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct RawEmpty {}
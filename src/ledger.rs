@@ -0,0 +1,226 @@
+//! Ledger devices
+//!
+//! Client of the Cosmos Ledger app, following the protocol implemented by
+//! `ledger-cosmos-go`. Commands are exchanged as APDUs over a [`Transport`];
+//! the only transport available is [`SpeculosTransport`], which talks to the
+//! Speculos emulator so that Ledger keys can be exercised without hardware.
+
+mod apdu;
+mod speculos;
+
+pub use self::apdu::{ApduAnswer, ApduCommand, Transport, MAX_DATA_LEN, SW_OK};
+pub use self::speculos::{SpeculosTransport, DEFAULT_SPECULOS_ADDR};
+
+use crate::crypto::PubKey;
+use crate::error::{Error, ErrorKind};
+use crate::hd::BIP44_PURPOSE;
+use crate::keyring::Bip44Params;
+use abscissa_core::{fail, format_err};
+use k256::ecdsa::Signature;
+use std::{env, fmt};
+
+/// Instruction class of the Cosmos app
+pub const CLA: u8 = 0x55;
+
+/// Get the version of the app
+pub const INS_GET_VERSION: u8 = 0x00;
+
+/// Sign a transaction with a secp256k1 key
+pub const INS_SIGN_SECP256K1: u8 = 0x02;
+
+/// Get the secp256k1 public key and address at a path
+pub const INS_GET_ADDR_SECP256K1: u8 = 0x04;
+
+/// Environment variable holding the address of the Speculos APDU server
+pub const SPECULOS_ADDR_ENV: &str = "AMON_SPECULOS_ADDR";
+
+/// Oldest app version speaking the protocol implemented here
+const MIN_VERSION: (u8, u8, u8) = (2, 1, 0);
+
+/// `P1` of the first chunk of a message to sign
+const CHUNK_INIT: u8 = 0;
+
+/// `P1` of the intermediate chunks of a message to sign
+const CHUNK_ADD: u8 = 1;
+
+/// `P1` of the last chunk of a message to sign
+const CHUNK_LAST: u8 = 2;
+
+/// `P2` selecting Amino JSON sign mode
+const SIGN_MODE_JSON: u8 = 0;
+
+/// Size of the chunks messages to sign are split into
+const CHUNK_SIZE: usize = 250;
+
+/// Hardened child indices have this bit set
+const HARDENED: u32 = 1 << 31;
+
+/// Length of compressed secp256k1 public keys
+const PUB_KEY_LEN: usize = 33;
+
+/// Version of the Cosmos app
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Version {
+    /// Whether the app runs in test mode
+    pub test_mode: bool,
+    /// Major version
+    pub major: u8,
+    /// Minor version
+    pub minor: u8,
+    /// Patch version
+    pub patch: u8,
+    /// Whether the device is locked
+    pub locked: bool,
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Cosmos app running on a device reached through `T`
+#[derive(Debug)]
+pub struct CosmosApp<T> {
+    transport: T,
+}
+
+impl CosmosApp<SpeculosTransport> {
+    /// Connect to the Speculos emulator listening at the address given by
+    /// the [`SPECULOS_ADDR_ENV`] environment variable, or
+    /// [`DEFAULT_SPECULOS_ADDR`]
+    pub fn connect() -> Result<Self, Error> {
+        let addr = env::var(SPECULOS_ADDR_ENV).unwrap_or_else(|_| DEFAULT_SPECULOS_ADDR.to_owned());
+        CosmosApp::new(SpeculosTransport::connect(addr)?)
+    }
+}
+
+impl<T: Transport> CosmosApp<T> {
+    /// Cosmos app reached through `transport`, checking that it is open,
+    /// unlocked and recent enough
+    pub fn new(transport: T) -> Result<Self, Error> {
+        let mut app = CosmosApp { transport };
+
+        let version = app.version()?;
+        if version.locked {
+            fail!(ErrorKind::Ledger, "the device is locked");
+        }
+        if (version.major, version.minor, version.patch) < MIN_VERSION {
+            fail!(
+                ErrorKind::Ledger,
+                "Cosmos app version {} is not supported, please update to {}.{}.{} or later",
+                version,
+                MIN_VERSION.0,
+                MIN_VERSION.1,
+                MIN_VERSION.2
+            );
+        }
+
+        Ok(app)
+    }
+
+    /// Version of the app
+    pub fn version(&mut self) -> Result<Version, Error> {
+        let data = self.send(INS_GET_VERSION, 0, 0, Vec::new())?;
+        if data.len() < 4 {
+            fail!(ErrorKind::Ledger, "invalid version answer");
+        }
+
+        Ok(Version {
+            test_mode: data[0] != 0,
+            major: data[1],
+            minor: data[2],
+            patch: data[3],
+            locked: data.get(4).is_some_and(|locked| *locked != 0),
+        })
+    }
+
+    /// Public key at `path` and its address with the `hrp` prefix, as
+    /// computed by the device. With `show`, the device displays the address
+    /// and waits for the user to confirm it.
+    pub fn get_address_pub_key(
+        &mut self,
+        path: &Bip44Params,
+        hrp: &str,
+        show: bool,
+    ) -> Result<(PubKey, String), Error> {
+        if hrp.is_empty() || hrp.len() > 83 || !hrp.is_ascii() {
+            fail!(ErrorKind::Ledger, "invalid bech32 prefix {}", hrp);
+        }
+
+        let mut data = vec![hrp.len() as u8];
+        data.extend_from_slice(hrp.as_bytes());
+        data.extend_from_slice(&serialize_path(path)?);
+
+        let answer = self.send(INS_GET_ADDR_SECP256K1, show as u8, 0, data)?;
+        if answer.len() < PUB_KEY_LEN {
+            fail!(ErrorKind::Ledger, "invalid address answer");
+        }
+
+        let pub_key = PubKey::secp256k1(&answer[..PUB_KEY_LEN])?;
+        let address = String::from_utf8(answer[PUB_KEY_LEN..].to_vec())
+            .map_err(|_| format_err!(ErrorKind::Ledger, "invalid address answer"))?;
+        Ok((pub_key, address))
+    }
+
+    /// Sign `message`, an Amino JSON sign document, with the key at `path`.
+    /// The device displays the message and waits for the user to approve it.
+    pub fn sign_secp256k1(
+        &mut self,
+        path: &Bip44Params,
+        message: &[u8],
+    ) -> Result<Signature, Error> {
+        if message.is_empty() {
+            fail!(ErrorKind::Ledger, "empty message");
+        }
+
+        self.send(
+            INS_SIGN_SECP256K1,
+            CHUNK_INIT,
+            SIGN_MODE_JSON,
+            serialize_path(path)?,
+        )?;
+
+        let mut der = Vec::new();
+        let chunks = message.chunks(CHUNK_SIZE).collect::<Vec<_>>();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let p1 = match i + 1 == chunks.len() {
+                true => CHUNK_LAST,
+                false => CHUNK_ADD,
+            };
+            der = self.send(INS_SIGN_SECP256K1, p1, SIGN_MODE_JSON, chunk.to_vec())?;
+        }
+
+        let signature = Signature::from_der(&der).map_err(|e| ErrorKind::Ledger.context(e))?;
+        Ok(signature.normalize_s().unwrap_or(signature))
+    }
+
+    /// Send a command to the app and return its response data
+    fn send(&mut self, ins: u8, p1: u8, p2: u8, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let command = ApduCommand {
+            cla: CLA,
+            ins,
+            p1,
+            p2,
+            data,
+        };
+        self.transport.exchange(&command)?.into_result()
+    }
+}
+
+/// Serialize a BIP44 path as the app expects it: its five components as
+/// little-endian integers, the first three hardened
+fn serialize_path(path: &Bip44Params) -> Result<Vec<u8>, Error> {
+    if path.purpose != BIP44_PURPOSE {
+        fail!(ErrorKind::Ledger, "{}: purpose must be 44", path);
+    }
+
+    let components = [
+        path.purpose | HARDENED,
+        path.coin_type | HARDENED,
+        path.account | HARDENED,
+        path.change as u32,
+        path.address_index,
+    ];
+    Ok(components.iter().flat_map(|c| c.to_le_bytes()).collect())
+}
@@ -0,0 +1,110 @@
+//! APDU framing as used by Ledger apps: short ISO 7816-4 commands, answered
+//! with response data followed by a 2-byte status word.
+
+use crate::error::{Error, ErrorKind};
+use abscissa_core::{fail, format_err};
+
+/// Status word of successful commands
+pub const SW_OK: u16 = 0x9000;
+
+/// Longest payload of a short APDU command
+pub const MAX_DATA_LEN: usize = 255;
+
+/// APDU command
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ApduCommand {
+    /// Instruction class of the app
+    pub cla: u8,
+    /// Instruction code
+    pub ins: u8,
+    /// First parameter
+    pub p1: u8,
+    /// Second parameter
+    pub p2: u8,
+    /// Payload, at most [`MAX_DATA_LEN`] bytes
+    pub data: Vec<u8>,
+}
+
+impl ApduCommand {
+    /// Serialize as `CLA INS P1 P2 Lc data`
+    pub fn encode(&self) -> Result<Vec<u8>, Error> {
+        if self.data.len() > MAX_DATA_LEN {
+            fail!(
+                ErrorKind::Ledger,
+                "APDU payload of {} bytes exceeds {} bytes",
+                self.data.len(),
+                MAX_DATA_LEN
+            );
+        }
+
+        let mut encoded = vec![self.cla, self.ins, self.p1, self.p2, self.data.len() as u8];
+        encoded.extend_from_slice(&self.data);
+        Ok(encoded)
+    }
+}
+
+/// APDU answer
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ApduAnswer {
+    /// Response data
+    pub data: Vec<u8>,
+    /// Status word
+    pub status: u16,
+}
+
+impl ApduAnswer {
+    /// Split a raw answer into its data and trailing status word
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < 2 {
+            fail!(ErrorKind::Ledger, "APDU answer too short");
+        }
+
+        let (data, status) = bytes.split_at(bytes.len() - 2);
+        Ok(ApduAnswer {
+            data: data.to_vec(),
+            status: u16::from_be_bytes([status[0], status[1]]),
+        })
+    }
+
+    /// Response data of a successful command, or the error matching its
+    /// status word
+    pub fn into_result(self) -> Result<Vec<u8>, Error> {
+        if self.status == SW_OK {
+            return Ok(self.data);
+        }
+
+        Err(format_err!(
+            ErrorKind::Ledger,
+            "{} (status {:#06x})",
+            status_message(self.status),
+            self.status
+        )
+        .into())
+    }
+}
+
+/// Meaning of the status words returned by the Cosmos app
+fn status_message(status: u16) -> &'static str {
+    match status {
+        0x6400 => "execution error",
+        0x6700 => "wrong length",
+        0x6982 => "empty buffer",
+        0x6983 => "output buffer too small",
+        0x6984 => "data is invalid",
+        0x6985 => "conditions not satisfied",
+        0x6986 => "transaction rejected",
+        0x6a80 => "bad key handle",
+        0x6b00 => "invalid P1/P2",
+        0x6d00 => "instruction not supported",
+        0x6e00 | 0x6e01 => "the Cosmos app does not seem to be open",
+        0x6f00 => "unknown error",
+        0x6f01 => "sign/verify error",
+        _ => "unexpected status",
+    }
+}
+
+/// Channel to a device
+pub trait Transport {
+    /// Send `command` and wait for the device's answer
+    fn exchange(&mut self, command: &ApduCommand) -> Result<ApduAnswer, Error>;
+}
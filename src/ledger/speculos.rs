@@ -0,0 +1,53 @@
+//! TCP transport to the [Speculos] emulator, for testing without hardware.
+//!
+//! Speculos exposes an APDU server (port 9999 by default) framing each
+//! message with its length as a 4-byte big-endian integer. Answers carry
+//! their response data, whose length is the one in the frame header,
+//! followed by the 2-byte status word.
+//!
+//! [Speculos]: https://github.com/LedgerHQ/speculos
+
+use super::apdu::{ApduAnswer, ApduCommand, Transport};
+use crate::error::{Error, ErrorKind};
+use abscissa_core::format_err;
+use std::{
+    convert::TryFrom,
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+};
+
+/// Default address of the Speculos APDU server
+pub const DEFAULT_SPECULOS_ADDR: &str = "127.0.0.1:9999";
+
+/// Connection to a Speculos APDU server
+#[derive(Debug)]
+pub struct SpeculosTransport {
+    stream: TcpStream,
+}
+
+impl SpeculosTransport {
+    /// Connect to the APDU server listening at `addr`
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self, Error> {
+        let stream = TcpStream::connect(addr)
+            .map_err(|e| format_err!(ErrorKind::Ledger, "cannot connect to Speculos: {}", e))?;
+        Ok(SpeculosTransport { stream })
+    }
+}
+
+impl Transport for SpeculosTransport {
+    fn exchange(&mut self, command: &ApduCommand) -> Result<ApduAnswer, Error> {
+        let apdu = command.encode()?;
+        let len = u32::try_from(apdu.len()).expect("APDU length fits in 32 bits");
+
+        let mut frame = len.to_be_bytes().to_vec();
+        frame.extend_from_slice(&apdu);
+        self.stream.write_all(&frame)?;
+
+        let mut len = [0u8; 4];
+        self.stream.read_exact(&mut len)?;
+        let mut answer = vec![0u8; u32::from_be_bytes(len) as usize + 2];
+        self.stream.read_exact(&mut answer)?;
+
+        ApduAnswer::from_bytes(&answer)
+    }
+}
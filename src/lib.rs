@@ -16,8 +16,16 @@
 )]
 #![allow(unused_imports)] // todo
 
+pub mod address;
 pub mod application;
+// `#[derive(Command, Runnable)]` from abscissa_derive 0.7 expands to impls
+// nested inside anonymous consts, which newer compilers flag
+#[allow(non_local_definitions)]
 pub mod commands;
 pub mod config;
+pub mod crypto;
 pub mod error;
+pub mod hd;
+pub mod keyring;
+pub mod ledger;
 pub mod prelude;
@@ -0,0 +1,49 @@
+//! Bech32 address tests
+
+#![forbid(unsafe_code)]
+#![warn(
+    missing_docs,
+    rust_2018_idioms,
+    trivial_casts,
+    unused_lifetimes,
+    unused_qualifications
+)]
+
+use amon::address::{self, AddressKind};
+use amon::crypto::PubKey;
+use k256::ecdsa::VerifyingKey;
+
+fn pub_key() -> PubKey {
+    let bytes =
+        hex::decode("024f4e2ad99c34d60b9ba6283c9431a8418af8673212961f97a77b6377fcd05b62").unwrap();
+    PubKey::Secp256k1(VerifyingKey::from_sec1_bytes(&bytes).unwrap())
+}
+
+/// Account, validator operator and consensus addresses of the same key
+#[test]
+fn pub_key_addresses() {
+    let key = pub_key();
+    assert_eq!(
+        address::pub_key_address(&key, AddressKind::Acc, "cosmos").unwrap(),
+        "cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal4"
+    );
+    assert!(address::pub_key_address(&key, AddressKind::Val, "cosmos")
+        .unwrap()
+        .starts_with("cosmosvaloper1"));
+    assert!(address::pub_key_address(&key, AddressKind::Cons, "osmo")
+        .unwrap()
+        .starts_with("osmovalcons1"));
+}
+
+/// Decoding returns the prefix and the address bytes
+#[test]
+fn decode_address() {
+    let (hrp, bytes) = address::decode("cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal4").unwrap();
+    assert_eq!(hrp, "cosmos");
+    assert_eq!(
+        hex::encode(bytes),
+        "28ff5c6d57d8cfd492b6fb42614536ed648e01fd"
+    );
+
+    assert!(address::decode("cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal5").is_err());
+}
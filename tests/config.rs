@@ -0,0 +1,217 @@
+//! Configuration file tests
+
+#![forbid(unsafe_code)]
+#![warn(
+    missing_docs,
+    rust_2018_idioms,
+    trivial_casts,
+    unused_lifetimes,
+    unused_qualifications
+)]
+
+use abscissa_core::Config;
+use amon::config::{AmonConfig, GasPrice, LogLevel, OutputFormat};
+use amon::keyring::KeyringBackend;
+use serde_json::Value;
+use std::{fs, process::Command};
+
+/// Configuration with two chains
+const EXAMPLE: &str = r#"
+[global]
+keyring_backend = "test"
+output = "json"
+log_level = "warn"
+
+[[chains]]
+id = "cosmoshub-4"
+bech32_prefix = "cosmos"
+rpc_addr = "https://rpc.cosmos.network:443"
+grpc_addr = "https://grpc.cosmos.network:443"
+gas_price = { price = 0.025, denom = "uatom" }
+
+[[chains]]
+id = "osmosis-1"
+bech32_prefix = "osmo"
+coin_type = 118
+gas_price = { price = 0.0025, denom = "uosmo" }
+"#;
+
+/// Parse `toml`, then let `f` modify the resulting configuration
+fn modified(toml: &str, f: impl FnOnce(&mut AmonConfig)) -> AmonConfig {
+    let mut config = AmonConfig::load_toml(toml).unwrap();
+    f(&mut config);
+    config
+}
+
+/// The example configuration loads with its defaults filled in
+#[test]
+fn load_example() {
+    let config = AmonConfig::load_toml(EXAMPLE).unwrap();
+    config.validate().unwrap();
+
+    assert_eq!(config.global.keyring_backend, KeyringBackend::Test);
+    assert_eq!(config.global.output, OutputFormat::Json);
+    assert_eq!(config.global.log_level, LogLevel::Warn);
+    assert_eq!(config.global.keyring_dir, None);
+
+    assert_eq!(config.chains.len(), 2);
+    let osmosis = config.find_chain("osmosis-1").unwrap();
+    assert_eq!(osmosis.bech32_prefix, "osmo");
+    assert_eq!(osmosis.coin_type, Some(118));
+    assert_eq!(config.chains[0].coin_type, None);
+    assert_eq!(osmosis.rpc_addr, "http://127.0.0.1:26657");
+    assert_eq!(
+        osmosis.gas_price,
+        GasPrice {
+            price: 0.0025,
+            denom: "uosmo".to_owned()
+        }
+    );
+    assert!(config.find_chain("juno-1").is_none());
+
+    let empty = AmonConfig::load_toml("").unwrap();
+    assert!(empty.chains.is_empty());
+    empty.validate().unwrap();
+
+    assert!(AmonConfig::load_toml("[global]\nkeyring = \"file\"").is_err());
+    assert!(AmonConfig::load_toml("[global]\noutput = \"xml\"").is_err());
+}
+
+/// Invalid chain settings are rejected
+#[test]
+fn validate_chains() {
+    let invalid = [
+        modified(EXAMPLE, |c| c.chains[0].id = " ".to_owned()),
+        modified(EXAMPLE, |c| c.chains[1].id = "cosmoshub-4".to_owned()),
+        modified(EXAMPLE, |c| c.chains[0].bech32_prefix = "Cosmos".to_owned()),
+        modified(EXAMPLE, |c| c.chains[0].bech32_prefix = String::new()),
+        modified(EXAMPLE, |c| c.chains[0].coin_type = Some(1 << 31)),
+        modified(EXAMPLE, |c| c.chains[0].rpc_addr = "localhost".to_owned()),
+        modified(EXAMPLE, |c| c.chains[0].grpc_addr = "http://".to_owned()),
+        modified(EXAMPLE, |c| c.chains[0].gas_price.price = -1.0),
+        modified(EXAMPLE, |c| c.chains[0].gas_price.price = f64::NAN),
+        modified(EXAMPLE, |c| {
+            c.chains[0].gas_price.denom = "1atom".to_owned()
+        }),
+        modified(EXAMPLE, |c| c.chains[0].gas_price.denom = "u".to_owned()),
+        modified(EXAMPLE, |c| c.chain.bech32_prefix = "a b".to_owned()),
+    ];
+    for config in invalid {
+        assert!(config.validate().is_err(), "{:?}", config);
+    }
+
+    let ibc = modified(EXAMPLE, |c| {
+        c.chains[0].gas_price.denom =
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_owned()
+    });
+    ibc.validate().unwrap();
+}
+
+/// Commands use the configuration in the home directory and the chain
+/// selected with `--chain`
+#[test]
+fn chain_selection() {
+    let home = tempfile::tempdir().unwrap();
+    fs::create_dir(home.path().join(".amon")).unwrap();
+    fs::write(home.path().join(".amon/config.toml"), EXAMPLE).unwrap();
+
+    let amon = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_amon"))
+            .args(args)
+            .env("HOME", home.path())
+            .output()
+            .unwrap()
+    };
+    let address = |out: &[u8]| -> String {
+        let json: Value = serde_json::from_slice(out).unwrap();
+        json["address"].as_str().unwrap().to_owned()
+    };
+
+    // json output and test backend from the configuration, first chain
+    let out = amon(&["keys", "add", "alice"]);
+    assert!(out.status.success(), "{:?}", out);
+    assert!(address(&out.stdout).starts_with("cosmos1"));
+    assert!(home.path().join(".amon/keyring-test").is_dir());
+
+    let out = amon(&["--chain", "osmosis-1", "keys", "show", "alice"]);
+    assert!(out.status.success(), "{:?}", out);
+    assert!(address(&out.stdout).starts_with("osmo1"));
+
+    let out = amon(&["--chain", "juno-1", "keys", "list"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("unknown chain juno-1"));
+
+    fs::write(
+        home.path().join(".amon/config.toml"),
+        EXAMPLE.replace("\"osmo\"", "\"OSMO\""),
+    )
+    .unwrap();
+    let out = amon(&["keys", "list"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("invalid bech32 prefix"));
+}
+
+/// The home directory is taken from `--home`, then `AMON_HOME`, and holds
+/// both the configuration and the keyrings, unless `--config` points at
+/// another configuration file
+#[test]
+fn home_directory() {
+    let home = tempfile::tempdir().unwrap();
+    let amon_home = tempfile::tempdir().unwrap();
+    let other = tempfile::tempdir().unwrap();
+    fs::write(amon_home.path().join("config.toml"), EXAMPLE).unwrap();
+
+    let amon = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_amon"))
+            .args(args)
+            .env("HOME", home.path())
+            .env("AMON_HOME", amon_home.path())
+            .output()
+            .unwrap()
+    };
+
+    // configuration and keyring from AMON_HOME
+    let out = amon(&["keys", "add", "alice", "--no-backup"]);
+    assert!(out.status.success(), "{:?}", out);
+    serde_json::from_slice::<Value>(&out.stdout).unwrap();
+    assert!(amon_home.path().join("keyring-test").is_dir());
+    assert!(!home.path().join(".amon").exists());
+
+    // no configuration in --home: defaults, without complaining
+    let other_home = other.path().to_str().unwrap();
+    let out = amon(&[
+        "--home",
+        other_home,
+        "--keyring-backend",
+        "test",
+        "keys",
+        "add",
+        "bob",
+        "--no-backup",
+    ]);
+    assert!(out.status.success(), "{:?}", out);
+    assert!(out.stderr.is_empty(), "{:?}", out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("address: cosmos1"));
+    assert!(other.path().join("keyring-test").is_dir());
+
+    // explicit configuration file, keyrings still in the home directory
+    let config = other.path().join("gaia.toml");
+    fs::write(&config, EXAMPLE.replace("\"cosmos\"", "\"gaia\"")).unwrap();
+    let out = amon(&[
+        "-c",
+        config.to_str().unwrap(),
+        "--home",
+        other_home,
+        "keys",
+        "show",
+        "bob",
+        "--address",
+    ]);
+    assert!(out.status.success(), "{:?}", out);
+    assert!(String::from_utf8_lossy(&out.stdout).starts_with("gaia1"));
+
+    // an explicit configuration file must exist
+    let missing = other.path().join("missing.toml");
+    let out = amon(&["-c", missing.to_str().unwrap(), "keys", "list"]);
+    assert!(!out.status.success());
+}
@@ -0,0 +1,141 @@
+//! Key encoding tests
+
+#![forbid(unsafe_code)]
+#![warn(
+    missing_docs,
+    rust_2018_idioms,
+    trivial_casts,
+    unused_lifetimes,
+    unused_qualifications
+)]
+
+use amon::crypto::{amino, armor, LegacyAminoPubKey, PrivKey, PubKey, SigningAlgo};
+use k256::ecdsa::VerifyingKey;
+
+fn pub_key() -> PubKey {
+    let bytes =
+        hex::decode("024f4e2ad99c34d60b9ba6283c9431a8418af8673212961f97a77b6377fcd05b62").unwrap();
+    PubKey::Secp256k1(VerifyingKey::from_sec1_bytes(&bytes).unwrap())
+}
+
+/// Amino prefixes match the ones registered by Tendermint
+#[test]
+fn amino_prefixes() {
+    assert_eq!(
+        hex::encode(amino::prefix(amino::PUB_KEY_SECP256K1)),
+        "eb5ae987"
+    );
+    assert_eq!(
+        hex::encode(amino::prefix(amino::PRIV_KEY_SECP256K1)),
+        "e1b0f79b"
+    );
+    assert_eq!(
+        hex::encode(amino::prefix(amino::PUB_KEY_MULTISIG_THRESHOLD)),
+        "22c1f7e2"
+    );
+}
+
+/// Public keys round-trip through `Any` and render as SDK JSON
+#[test]
+fn pub_key_encoding() {
+    let key = pub_key();
+    assert_eq!(
+        hex::encode(key.to_amino()),
+        "eb5ae98721024f4e2ad99c34d60b9ba6283c9431a8418af8673212961f97a77b6377fcd05b62"
+    );
+    assert_eq!(PubKey::from_any(&key.to_any()).unwrap(), key);
+    assert_eq!(
+        key.to_json().to_string(),
+        r#"{"@type":"/cosmos.crypto.secp256k1.PubKey","key":"Ak9OKtmcNNYLm6YoPJQxqEGK+GcyEpYfl6d7Y3f80Fti"}"#
+    );
+    assert_eq!(PubKey::from_json(&key.to_json()).unwrap(), key);
+}
+
+/// Multisig keys are addressed by the hash of their Amino encoding
+#[test]
+fn multisig_pub_key() {
+    let key = PubKey::Multisig(LegacyAminoPubKey::new(1, vec![pub_key()]).unwrap());
+    assert_eq!(
+        hex::encode(key.to_amino()),
+        "22c1f7e208011226eb5ae98721024f4e2ad99c34d60b9ba6283c9431a8418af8673212961f97a77b6377fcd05b62"
+    );
+    assert_eq!(
+        hex::encode(key.address()),
+        "c79e1fe1bfd3b50bf73a3af49313265e1438a8ee"
+    );
+    assert_eq!(PubKey::from_any(&key.to_any()).unwrap(), key);
+    assert_eq!(PubKey::from_json(&key.to_json()).unwrap(), key);
+
+    assert!(LegacyAminoPubKey::new(0, vec![pub_key()]).is_err());
+    assert!(LegacyAminoPubKey::new(2, vec![pub_key()]).is_err());
+}
+
+/// Armor produced by the SDK decodes and re-encodes to the same body and
+/// checksum
+#[test]
+fn sdk_armor_encoding() {
+    let fixture = include_str!("fixtures/armor/sdk-secp256k1.armor");
+    let (block_type, headers, data) = armor::decode_armor(fixture).unwrap();
+    assert_eq!(block_type, armor::BLOCK_TYPE_PRIV_KEY);
+    assert_eq!(headers["salt"], "A790BB721D1C094260EA84F5E5B72289");
+    assert_eq!(data.len(), 77);
+
+    let encoded = armor::encode_armor(&block_type, &headers, &data);
+    let body = |armor: &str| armor.split("\n\n").nth(1).unwrap().trim_end().to_owned();
+    assert_eq!(body(&encoded), body(fixture));
+
+    let corrupted = fixture.replace("=f3l4", "=f3l5");
+    assert!(armor::decode_armor(&corrupted).is_err());
+}
+
+/// Armor produced by the SDK decrypts with its passphrase only
+#[test]
+fn sdk_armor_decryption() {
+    let fixture = include_str!("fixtures/armor/sdk-secp256k1.armor");
+    let priv_key = armor::unarmor_decrypt_priv_key(fixture, "123456789").unwrap();
+    assert_eq!(priv_key.algo(), "secp256k1");
+    assert_eq!(
+        hex::encode(priv_key.to_bytes()),
+        "2485e33678db4175dc0ecef2d6e1fc493d4a0d7f7ce83324b6ed70afe77f3485"
+    );
+
+    assert!(armor::unarmor_decrypt_priv_key(fixture, "987654321").is_err());
+}
+
+/// Keys of every algorithm survive an armor round trip
+#[test]
+fn armor_round_trip() {
+    let keys = [
+        PrivKey::secp256k1(&[1; 32]).unwrap(),
+        PrivKey::ed25519(&[2; 32]).unwrap(),
+        PrivKey::eth_secp256k1(&[3; 32]).unwrap(),
+    ];
+
+    for priv_key in &keys {
+        let armored = armor::encrypt_armor_priv_key(priv_key, "passphrase").unwrap();
+        let decrypted = armor::unarmor_decrypt_priv_key(&armored, "passphrase").unwrap();
+        assert_eq!(decrypted.to_bytes(), priv_key.to_bytes());
+        assert_eq!(decrypted.pub_key(), priv_key.pub_key());
+    }
+}
+
+/// Ethermint keys are addressed like Ethereum accounts (EIP-155 example key)
+#[test]
+fn eth_secp256k1_keys() {
+    let priv_key = PrivKey::from_bytes(SigningAlgo::EthSecp256k1, &[0x46; 32]).unwrap();
+    assert_eq!(priv_key.algo(), "eth_secp256k1");
+
+    let key = priv_key.pub_key();
+    assert_eq!(key.algo(), Some(SigningAlgo::EthSecp256k1));
+    assert_eq!(
+        hex::encode(key.address()),
+        "9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"
+    );
+    assert_eq!(PubKey::from_any(&key.to_any()).unwrap(), key);
+    assert_eq!(PubKey::from_amino(&key.to_amino()).unwrap(), key);
+    assert_eq!(PubKey::from_json(&key.to_json()).unwrap(), key);
+    assert_eq!(
+        PrivKey::from_any(&priv_key.to_any()).unwrap().pub_key(),
+        key
+    );
+}
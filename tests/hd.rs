@@ -0,0 +1,142 @@
+//! Key derivation tests.
+//!
+//! Expected keys were computed independently from the BIP39 seed and the
+//! BIP32 derivation rules.
+
+#![forbid(unsafe_code)]
+#![warn(
+    missing_docs,
+    rust_2018_idioms,
+    trivial_casts,
+    unused_lifetimes,
+    unused_qualifications
+)]
+
+use amon::crypto::SigningAlgo;
+use amon::error::ErrorKind;
+use amon::hd::{self, HdPath};
+use bip39::{Language, Mnemonic};
+
+fn path(s: &str) -> HdPath {
+    s.parse().unwrap()
+}
+
+const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon \
+                        abandon abandon abandon abandon abandon about";
+
+/// Derive the first account along the default Cosmos BIP44 path
+#[test]
+fn derive_secp256k1_default_path() {
+    let mnemonic = Mnemonic::from_phrase(MNEMONIC, Language::English).unwrap();
+    let path = HdPath::bip44(hd::DEFAULT_COIN_TYPE, 0, 0).unwrap();
+    assert_eq!(path.to_string(), "m/44'/118'/0'/0/0");
+
+    let key = hd::derive_secp256k1(&mnemonic, "", &path).unwrap();
+    assert_eq!(
+        hex::encode(key.to_bytes()),
+        "c4a48e2fce1481cd3294b4490f6678090ea98d3d0e5cd984558ab0968741b104"
+    );
+    assert_eq!(
+        hex::encode(key.verifying_key().to_sec1_bytes()),
+        "024f4e2ad99c34d60b9ba6283c9431a8418af8673212961f97a77b6377fcd05b62"
+    );
+}
+
+/// Generated mnemonics carry 256 bits of entropy
+#[test]
+fn new_mnemonic_has_24_words() {
+    let mnemonic = hd::new_mnemonic();
+    assert_eq!(mnemonic.phrase().split_whitespace().count(), 24);
+}
+
+/// Entropy maps to the BIP39 reference mnemonic
+#[test]
+fn mnemonic_from_entropy() {
+    let mnemonic = hd::mnemonic_from_entropy(&[0; 32]).unwrap();
+    assert_eq!(mnemonic.phrase(), format!("{}art", "abandon ".repeat(23)));
+
+    assert!(hd::mnemonic_from_entropy(&[0; 7]).is_err());
+}
+
+/// Ethermint keys derive like secp256k1 keys but get Ethereum addresses
+#[test]
+fn derive_eth_secp256k1() {
+    let mnemonic = Mnemonic::from_phrase(MNEMONIC, Language::English).unwrap();
+    let key = hd::derive(
+        SigningAlgo::EthSecp256k1,
+        &mnemonic,
+        "",
+        &path("m/44'/60'/0'/0/0"),
+    )
+    .unwrap();
+    assert_eq!(key.signing_algo(), SigningAlgo::EthSecp256k1);
+    assert_eq!(
+        hex::encode(key.pub_key().address()),
+        "9858effd232b4033e47d90003d41ec34ecaeda94"
+    );
+}
+
+/// ed25519 derivation follows SLIP-0010 (test vector 1)
+#[test]
+fn derive_ed25519_slip10() {
+    let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+    assert_eq!(
+        hex::encode(hd::derive_ed25519(&seed, &path("m")).unwrap()),
+        "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"
+    );
+    assert_eq!(
+        hex::encode(hd::derive_ed25519(&seed, &path("m/0'/1'/2'/2'/1000000000'")).unwrap()),
+        "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793"
+    );
+    assert!(hd::derive_ed25519(&seed, &path("m/0/1'")).is_err());
+}
+
+/// Paths parse from and render back to the `m/44'/118'/0'/0/0` notation
+#[test]
+fn hd_path_notation() {
+    let parsed = path("m/44h/118H/0'/0/0");
+    assert_eq!(parsed.to_string(), "m/44'/118'/0'/0/0");
+    assert_eq!(parsed, HdPath::bip44(118, 0, 0).unwrap());
+    assert_eq!(path("m").to_string(), "m");
+    assert_eq!(path("m/2147483647'").to_string(), "m/2147483647'");
+
+    for invalid in [
+        "",
+        "44'/118'/0'/0/0",
+        "m/",
+        "m/44'/",
+        "m/-1",
+        "m/+1",
+        "m/1''",
+        "m/0x10",
+        "m/2147483648",
+        "m/4294967296",
+    ] {
+        let err = invalid.parse::<HdPath>().unwrap_err();
+        assert_eq!(*err.kind(), ErrorKind::HdPath, "{:?}", invalid);
+    }
+
+    assert!(HdPath::bip44(118, 1 << 31, 0).is_err());
+    assert!(HdPath::bip44(118, 0, u32::MAX).is_err());
+}
+
+/// Only well-formed BIP44 paths convert to Ledger parameters
+#[test]
+fn hd_path_bip44_params() {
+    let params = path("m/44'/118'/2'/1/3").bip44_params().unwrap();
+    assert_eq!(params.coin_type, 118);
+    assert_eq!(params.account, 2);
+    assert!(params.change);
+    assert_eq!(params.address_index, 3);
+    assert_eq!(HdPath::from(&params).to_string(), "m/44'/118'/2'/1/3");
+
+    for invalid in [
+        "m/44'/118'/0'/0",
+        "m/49'/118'/0'/0/0",
+        "m/44'/118/0'/0/0",
+        "m/44'/118'/0'/0'/0",
+        "m/44'/118'/0'/2/0",
+    ] {
+        assert!(path(invalid).bip44_params().is_err(), "{}", invalid);
+    }
+}
@@ -0,0 +1,266 @@
+//! Keyring backend tests
+
+#![forbid(unsafe_code)]
+#![warn(
+    missing_docs,
+    rust_2018_idioms,
+    trivial_casts,
+    unused_lifetimes,
+    unused_qualifications
+)]
+
+use amon::crypto::PrivKey;
+use amon::keyring::{FileKeyring, KeyItem, KeyRecord, Keyring, MemoryKeyring, PassKeyring};
+use k256::ecdsa::SigningKey;
+use std::{io, process::Command};
+
+fn key_record(name: &str) -> KeyRecord {
+    let priv_key =
+        hex::decode("c4a48e2fce1481cd3294b4490f6678090ea98d3d0e5cd984558ab0968741b104").unwrap();
+    let priv_key = PrivKey::Secp256k1(SigningKey::from_slice(&priv_key).unwrap());
+    KeyRecord::new_local(name, priv_key)
+}
+
+/// Store, list and delete a key, creating the keyhash on first use
+#[test]
+fn file_keyring_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let keyring = FileKeyring::new(dir.path(), Box::new(|_| Ok("passphrase".to_owned())));
+
+    keyring.put(&key_record("alice")).unwrap();
+    assert!(dir.path().join("keyhash").exists());
+    assert!(dir.path().join("alice.info").exists());
+    assert!(dir
+        .path()
+        .join("28ff5c6d57d8cfd492b6fb42614536ed648e01fd.address")
+        .exists());
+
+    let keyring = FileKeyring::new(dir.path(), Box::new(|_| Ok("passphrase".to_owned())));
+    let names = keyring
+        .list()
+        .unwrap()
+        .into_iter()
+        .map(|info| info.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["alice"]);
+    assert_eq!(
+        keyring.get("alice").unwrap().pub_key,
+        key_record("alice").pub_key
+    );
+
+    keyring.delete("alice").unwrap();
+    assert!(!keyring.exists("alice").unwrap());
+    assert_eq!(keyring.item_keys().unwrap(), Vec::<String>::new());
+}
+
+/// A passphrase not matching the keyhash is refused
+#[test]
+fn file_keyring_wrong_passphrase() {
+    let dir = tempfile::tempdir().unwrap();
+    FileKeyring::new(dir.path(), Box::new(|_| Ok("passphrase".to_owned())))
+        .put(&key_record("alice"))
+        .unwrap();
+
+    let keyring = FileKeyring::new(
+        dir.path(),
+        Box::new(|_| Err(io::ErrorKind::UnexpectedEof.into())),
+    );
+    assert!(keyring.get("alice").is_err());
+
+    let keyring = FileKeyring::new(dir.path(), Box::new(|_| Ok("wrong".to_owned())));
+    assert!(keyring.get("alice").is_err());
+}
+
+/// Entries encrypted by another JOSE implementation, as written by the Go
+/// file backend, can be read back
+#[test]
+fn file_keyring_reads_foreign_entries() {
+    let keyring = FileKeyring::with_password("tests/fixtures/keyring-file", "12345678");
+
+    assert_eq!(
+        keyring.item("fixture.info").unwrap().unwrap(),
+        b"independently encrypted"
+    );
+    assert_eq!(keyring.item_keys().unwrap(), ["fixture.info"]);
+}
+
+/// The memory keyring keeps records and their address index
+#[test]
+fn memory_keyring_round_trip() {
+    let keyring = MemoryKeyring::new();
+    keyring.put(&key_record("alice")).unwrap();
+    keyring.put(&key_record("bob")).unwrap();
+
+    assert_eq!(keyring.get("bob").unwrap().name, "bob");
+    assert_eq!(
+        keyring
+            .item("28ff5c6d57d8cfd492b6fb42614536ed648e01fd.address")
+            .unwrap(),
+        Some(b"bob.info".to_vec())
+    );
+    for address in [
+        "cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal4",
+        "osmo19rl4cm2hmr8afy4kldpxz3fka4jguq0a5m7df8",
+    ] {
+        assert_eq!(keyring.get_by_address(address).unwrap().name, "bob");
+    }
+    assert!(keyring.get_by_address("bob").is_err());
+
+    keyring.delete("alice").unwrap();
+    let names = keyring
+        .list()
+        .unwrap()
+        .into_iter()
+        .map(|info| info.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["bob"]);
+}
+
+/// Store and read back keys through `pass`, with a throwaway GPG key and
+/// password store. Skipped when `pass` isn't installed.
+#[test]
+fn pass_keyring_round_trip() {
+    if Command::new(PassKeyring::COMMAND)
+        .arg("version")
+        .output()
+        .is_err()
+    {
+        eprintln!("{} not found, skipping", PassKeyring::COMMAND);
+        return;
+    }
+
+    let gnupg_home = tempfile::tempdir().unwrap();
+    let store = tempfile::tempdir().unwrap();
+
+    let gpg_id = "amon-test@example.com";
+    assert!(Command::new("gpg")
+        .args(["--batch", "--passphrase", "", "--quick-gen-key", gpg_id])
+        .args(["default", "default", "never"])
+        .env("GNUPGHOME", gnupg_home.path())
+        .status()
+        .unwrap()
+        .success());
+    assert!(Command::new(PassKeyring::COMMAND)
+        .args(["init", gpg_id])
+        .env("GNUPGHOME", gnupg_home.path())
+        .env("PASSWORD_STORE_DIR", store.path())
+        .status()
+        .unwrap()
+        .success());
+
+    let keyring = PassKeyring::new(Some(store.path().to_owned()), "keyring-amon")
+        .unwrap()
+        .with_gnupg_home(gnupg_home.path());
+    keyring.put(&key_record("alice")).unwrap();
+    assert!(store.path().join("keyring-amon/alice.info.gpg").exists());
+    assert_eq!(keyring.get("alice").unwrap().name, "alice");
+
+    keyring.delete("alice").unwrap();
+    assert!(!keyring.exists("alice").unwrap());
+    assert_eq!(keyring.item_keys().unwrap(), Vec::<String>::new());
+
+    // stop the agent started for the throwaway GNUPGHOME
+    let _ = Command::new("gpgconf")
+        .args(["--kill", "gpg-agent"])
+        .env("GNUPGHOME", gnupg_home.path())
+        .status();
+}
+
+/// Records are encoded like the SDK `keyring.Record` protobuf message
+#[test]
+fn record_encoding() {
+    let local = key_record("alice");
+    let decoded = KeyRecord::decode(&local.encode()).unwrap();
+    assert_eq!(decoded.name, "alice");
+    assert_eq!(decoded.key_type(), "local");
+    assert_eq!(decoded.pub_key, local.pub_key);
+    assert_eq!(
+        hex::encode(decoded.address()),
+        "28ff5c6d57d8cfd492b6fb42614536ed648e01fd"
+    );
+
+    // name, secp256k1 pubkey Any, empty offline item
+    let offline = KeyRecord::new_offline("alice", local.pub_key.clone());
+    let expected = [
+        "0a05616c696365",
+        "12460a1f2f636f736d6f732e63727970746f2e736563703235366b312e5075624b6579",
+        "12230a21024f4e2ad99c34d60b9ba6283c9431a8418af8673212961f97a77b6377fcd05b62",
+        "3200",
+    ]
+    .concat();
+    assert_eq!(hex::encode(offline.encode()), expected);
+    assert_eq!(
+        KeyRecord::decode(&hex::decode(expected).unwrap())
+            .unwrap()
+            .key_type(),
+        "offline"
+    );
+}
+
+/// Renaming moves the record and repoints its address index
+#[test]
+fn memory_keyring_rename() {
+    let keyring = MemoryKeyring::new();
+    keyring.put(&key_record("alice")).unwrap();
+    keyring.put(&key_record("carol")).unwrap();
+
+    keyring.rename("alice", "bob").unwrap();
+    assert!(!keyring.exists("alice").unwrap());
+    assert_eq!(keyring.get("bob").unwrap().name, "bob");
+    assert_eq!(
+        keyring
+            .item("28ff5c6d57d8cfd492b6fb42614536ed648e01fd.address")
+            .unwrap(),
+        Some(b"bob.info".to_vec())
+    );
+
+    assert!(keyring.rename("bob", "carol").is_err());
+    assert!(keyring.exists("bob").unwrap());
+}
+
+/// Legacy Amino records left by SDK versions before v0.46 are rewritten as
+/// protobuf records
+#[test]
+fn legacy_records_migrate() {
+    let keyring = MemoryKeyring::new();
+    for name in ["local", "ledger", "offline", "multi"] {
+        let fixture = std::fs::read(format!("tests/fixtures/legacy/{}.info", name)).unwrap();
+        keyring
+            .set_item(&format!("{}.info", name), &fixture)
+            .unwrap();
+    }
+
+    assert!(keyring.get("local").is_err());
+
+    for name in ["local", "ledger", "offline", "multi"] {
+        assert!(keyring.migrate(name).unwrap());
+        assert!(!keyring.migrate(name).unwrap());
+
+        let record = keyring.get(name).unwrap();
+        assert_eq!(record.name, name);
+        assert_eq!(record.key_type(), name);
+        assert_eq!(
+            keyring
+                .item(&format!("{}.address", hex::encode(record.address())))
+                .unwrap(),
+            Some(format!("{}.info", name).into_bytes())
+        );
+    }
+
+    assert_eq!(
+        hex::encode(keyring.get("local").unwrap().address()),
+        "28ff5c6d57d8cfd492b6fb42614536ed648e01fd"
+    );
+    assert_eq!(
+        hex::encode(keyring.get("offline").unwrap().address()),
+        "ebc0ee0b2ab9e8277a600c251475e22a3241a1c1"
+    );
+    assert_eq!(
+        hex::encode(keyring.get("multi").unwrap().address()),
+        "1a8e07ace00bc3437804a0022c192440b904374c"
+    );
+    match keyring.get("ledger").unwrap().item {
+        KeyItem::Ledger(path) => assert_eq!(path.to_string(), "m/44'/118'/0'/0/0"),
+        _ => panic!("not a ledger record"),
+    }
+}
@@ -0,0 +1,918 @@
+//! Acceptance tests for the `keys` subcommands, run against the `test`
+//! keyring backend in a temporary directory.
+
+#![forbid(unsafe_code)]
+#![warn(
+    missing_docs,
+    rust_2018_idioms,
+    trivial_casts,
+    unused_lifetimes,
+    unused_qualifications
+)]
+
+use abscissa_core::testing::{prelude::*, process::Process};
+use amon::crypto::{armor, PubKey, SigningAlgo};
+use amon::hd::{self, HdPath};
+use amon::keyring::{FileKeyring, KeyItem, KeyRecord, Keyring};
+use k256::ecdsa::VerifyingKey;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::{
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    process::{Command, Output, Stdio},
+};
+
+/// Executes the application binary via `cargo run`, serializing invocations.
+pub static RUNNER: Lazy<CmdRunner> = Lazy::new(CmdRunner::default);
+
+/// Runner for `keys` subcommands using the test backend in `dir`
+fn keys_runner(dir: &Path) -> CmdRunner {
+    let mut runner = RUNNER.clone();
+    runner.args([
+        "--keyring-backend",
+        "test",
+        "--keyring-dir",
+        dir.to_str().unwrap(),
+        "keys",
+    ]);
+    runner
+}
+
+/// Run `keys` subcommands with the test backend in `dir`, feeding `input`
+/// to the prompts through piped stdin
+fn keys_with_input(dir: &Path, args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_amon"))
+        .args(["--keyring-backend", "test", "--keyring-dir"])
+        .arg(dir)
+        .arg("keys")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Read the JSON document printed by the command, skipping log lines
+fn read_json(cmd: &mut Process<'_>) -> Value {
+    let stdout: &mut BufReader<_> = cmd.stdout();
+    let line = stdout
+        .lines()
+        .map(Result::unwrap)
+        .find(|line| line.starts_with(&['{', '['][..]))
+        .expect("no JSON output");
+
+    serde_json::from_str(&line).unwrap()
+}
+
+/// `keys add` stores the key without prompting
+#[test]
+fn add_with_test_backend() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner
+        .args(["add", "validator", "--output", "json"])
+        .capture_stdout()
+        .run();
+
+    let out = read_json(&mut cmd);
+    cmd.wait().unwrap().expect_success();
+    assert_eq!(out["name"], "validator");
+    assert!(out["address"].as_str().unwrap().starts_with("cosmos1"));
+    assert_eq!(out["mnemonic"].as_str().unwrap().split(' ').count(), 24);
+
+    let keyring = FileKeyring::new_test(dir.path().join("keyring-test"));
+    let record = keyring.get("validator").unwrap();
+    assert_eq!(record.key_type(), "local");
+    assert_eq!(record.pub_key.to_json().to_string(), out["pubkey"]);
+}
+
+/// `keys add --dry-run` leaves the keyring untouched
+#[test]
+fn add_dry_run() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner
+        .args([
+            "add",
+            "validator",
+            "--dry-run",
+            "--bech32-prefix",
+            "osmo",
+            "--output",
+            "json",
+        ])
+        .capture_stdout()
+        .run();
+
+    let out = read_json(&mut cmd);
+    assert_eq!(out["name"], "validator");
+    assert!(out["address"].as_str().unwrap().starts_with("osmo1"));
+    cmd.wait().unwrap().expect_success();
+    assert!(!dir.path().join("keyring-test").exists());
+}
+
+/// `keys add` only replaces an existing key once the new one is ready and
+/// the user agreed to override it
+#[test]
+fn override_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let keyring = FileKeyring::new_test(dir.path().join("keyring-test"));
+    let out = keys_with_input(dir.path(), &["add", "alice", "--no-backup"], "");
+    assert!(out.status.success());
+    let original = keyring.get("alice").unwrap().pub_key;
+
+    let out = keys_with_input(dir.path(), &["add", "alice", "--pubkey", "not json"], "y\n");
+    assert!(!out.status.success());
+    assert_eq!(keyring.get("alice").unwrap().pub_key, original);
+
+    let out = keys_with_input(dir.path(), &["add", "alice", "--no-backup"], "n\n");
+    assert!(!out.status.success());
+    assert_eq!(keyring.get("alice").unwrap().pub_key, original);
+
+    let out = keys_with_input(dir.path(), &["add", "alice", "--no-backup"], "y\n");
+    assert!(out.status.success());
+    assert_ne!(keyring.get("alice").unwrap().pub_key, original);
+    assert_eq!(keyring.names().unwrap(), ["alice"]);
+}
+
+/// `keys list` prints every record sorted by name
+#[test]
+fn list_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["bob", "alice"] {
+        let mut runner = keys_runner(dir.path());
+        let cmd = runner.args(["add", name, "--no-backup"]).run();
+        cmd.wait().unwrap().expect_success();
+    }
+
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner
+        .args(["list", "--output", "json"])
+        .capture_stdout()
+        .run();
+    let out = read_json(&mut cmd);
+    cmd.wait().unwrap().expect_success();
+    let names = out
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|key| key["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["alice", "bob"]);
+    assert_eq!(out[0]["type"], "local");
+
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner.args(["list", "--list-names"]).capture_stdout().run();
+    let stdout: &mut BufReader<_> = cmd.stdout();
+    let lines = stdout.lines().map(Result::unwrap).collect::<Vec<_>>();
+    cmd.wait().unwrap().expect_success();
+    assert!(lines.ends_with(&["alice".to_owned(), "bob".to_owned()]));
+}
+
+/// `keys list` on an empty keyring prints an empty list in structured
+/// formats and a notice otherwise
+#[test]
+fn list_empty_keyring() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let out = keys_with_input(dir.path(), &["list", "--output", "json"], "");
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "[]\n");
+
+    let out = keys_with_input(dir.path(), &["list", "--output", "yaml"], "");
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "[]\n");
+
+    let out = keys_with_input(dir.path(), &["list"], "");
+    assert!(out.status.success());
+    assert!(out.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("No records were found in keyring"));
+}
+
+/// `keys show` prints single keys and ephemeral multisig keys
+#[test]
+fn show_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["alice", "bob"] {
+        let mut runner = keys_runner(dir.path());
+        let cmd = runner.args(["add", name, "--no-backup"]).run();
+        cmd.wait().unwrap().expect_success();
+    }
+
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner
+        .args(["show", "alice", "--output", "json"])
+        .capture_stdout()
+        .run();
+    let alice = read_json(&mut cmd);
+    cmd.wait().unwrap().expect_success();
+    assert_eq!(alice["name"], "alice");
+
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner
+        .args(["show", "alice", "--address"])
+        .capture_stdout()
+        .run();
+    let stdout: &mut BufReader<_> = cmd.stdout();
+    let address = stdout.lines().last().unwrap().unwrap();
+    cmd.wait().unwrap().expect_success();
+    assert_eq!(alice["address"], address);
+
+    // keys are also found by any of their addresses
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner
+        .args(["show", &address, "--output", "json"])
+        .capture_stdout()
+        .run();
+    let out = read_json(&mut cmd);
+    cmd.wait().unwrap().expect_success();
+    assert_eq!(out["name"], "alice");
+
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner
+        .args(["show", "cosmos1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqnrql8a"])
+        .run();
+    cmd.wait().unwrap().expect_code(1);
+
+    for flag in ["--address", "--pubkey"] {
+        let mut runner = keys_runner(dir.path());
+        let cmd = runner
+            .args(["show", "alice", flag, "--output", "json"])
+            .run();
+        cmd.wait().unwrap().expect_code(2);
+    }
+
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner
+        .args(["show", "alice", "--bech", "val", "--output", "json"])
+        .capture_stdout()
+        .run();
+    let out = read_json(&mut cmd);
+    cmd.wait().unwrap().expect_success();
+    assert!(out["address"]
+        .as_str()
+        .unwrap()
+        .starts_with("cosmosvaloper1"));
+
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner
+        .args([
+            "show",
+            "alice",
+            "bob",
+            "--multisig-threshold",
+            "2",
+            "--output",
+            "json",
+        ])
+        .capture_stdout()
+        .run();
+    let out = read_json(&mut cmd);
+    cmd.wait().unwrap().expect_success();
+    assert_eq!(out["name"], "multi");
+    assert_eq!(out["type"], "multi");
+
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner
+        .args(["show", "alice", "bob", "--multisig-threshold", "3"])
+        .capture_stdout()
+        .run();
+    cmd.wait().unwrap().expect_code(1);
+}
+
+/// `keys delete` removes keys and their address index, keeping references
+/// unless forced
+#[test]
+fn delete_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner.args(["add", "alice", "--no-backup"]).run();
+    cmd.wait().unwrap().expect_success();
+
+    let keyring = FileKeyring::new_test(dir.path().join("keyring-test"));
+    let pub_key =
+        hex::decode("024f4e2ad99c34d60b9ba6283c9431a8418af8673212961f97a77b6377fcd05b62").unwrap();
+    let pub_key = PubKey::Secp256k1(VerifyingKey::from_sec1_bytes(&pub_key).unwrap());
+    keyring
+        .put(&KeyRecord::new_offline("bob", pub_key))
+        .unwrap();
+
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner.args(["delete", "alice", "bob", "--yes"]).run();
+    cmd.wait().unwrap().expect_code(1);
+    assert!(!keyring.exists("alice").unwrap());
+    assert!(keyring.exists("bob").unwrap());
+
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner.args(["delete", "bob", "--yes", "--force"]).run();
+    cmd.wait().unwrap().expect_success();
+    assert!(!keyring.exists("bob").unwrap());
+    assert!(keyring
+        .item_keys()
+        .unwrap()
+        .iter()
+        .all(|key| !key.ends_with(".address")));
+}
+
+/// `keys rename` moves a key, replacing an existing one only when confirmed
+#[test]
+fn rename_key() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["alice", "bob"] {
+        let mut runner = keys_runner(dir.path());
+        let cmd = runner.args(["add", name, "--no-backup"]).run();
+        cmd.wait().unwrap().expect_success();
+    }
+
+    let keyring = FileKeyring::new_test(dir.path().join("keyring-test"));
+    let alice = keyring.get("alice").unwrap();
+
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner.args(["rename", "alice", "carol"]).run();
+    cmd.wait().unwrap().expect_success();
+    assert!(!keyring.exists("alice").unwrap());
+    assert_eq!(keyring.get("carol").unwrap().pub_key, alice.pub_key);
+
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner.args(["rename", "carol", "bob", "--yes"]).run();
+    cmd.wait().unwrap().expect_success();
+    assert!(!keyring.exists("carol").unwrap());
+    assert_eq!(keyring.get("bob").unwrap().pub_key, alice.pub_key);
+    assert_eq!(keyring.list().unwrap().len(), 1);
+}
+
+/// `keys export` armors the key, or prints it as hex once confirmed
+#[test]
+fn export_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner.args(["add", "alice", "--no-backup"]).run();
+    cmd.wait().unwrap().expect_success();
+
+    let out = keys_with_input(dir.path(), &["export", "alice"], "passphrase\n");
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    let armored = &stdout[stdout.find("-----BEGIN").unwrap()..];
+    let (block_type, headers, _) = armor::decode_armor(armored).unwrap();
+    assert_eq!(block_type, "TENDERMINT PRIVATE KEY");
+    assert_eq!(headers["kdf"], "bcrypt");
+    assert_eq!(headers["type"], "secp256k1");
+    assert_eq!(headers["salt"].len(), 32);
+
+    let keyring = FileKeyring::new_test(dir.path().join("keyring-test"));
+    let priv_key = match keyring.get("alice").unwrap().item {
+        KeyItem::Local(priv_key) => priv_key,
+        _ => unreachable!(),
+    };
+    let args = ["export", "alice", "--unarmored-hex", "--unsafe"];
+    let out = keys_with_input(dir.path(), &args, "y\n");
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.ends_with(&format!("{}\n", hex::encode(priv_key.to_bytes()))));
+
+    let out = keys_with_input(dir.path(), &args, "n\n");
+    assert!(out.status.success());
+    assert!(!String::from_utf8(out.stdout)
+        .unwrap()
+        .contains(&hex::encode(priv_key.to_bytes())));
+
+    let out = keys_with_input(dir.path(), &["export", "alice", "--unsafe"], "");
+    assert!(!out.status.success());
+}
+
+/// `keys import` decrypts armor written by the SDK and by `keys export`
+#[test]
+fn import_armored_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let fixture =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/armor/sdk-secp256k1.armor");
+    let fixture = fixture.to_str().unwrap();
+
+    let out = keys_with_input(dir.path(), &["import", "sdk", fixture], "wrong\n");
+    assert!(!out.status.success());
+
+    let out = keys_with_input(dir.path(), &["import", "sdk", fixture], "123456789\n");
+    assert!(out.status.success());
+    let keyring = FileKeyring::new_test(dir.path().join("keyring-test"));
+    assert_eq!(keyring.get("sdk").unwrap().key_type(), "local");
+
+    // names are never overwritten
+    let out = keys_with_input(dir.path(), &["import", "sdk", fixture], "123456789\n");
+    assert!(!out.status.success());
+
+    let out = keys_with_input(dir.path(), &["export", "sdk"], "passphrase\n");
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    let armored = dir.path().join("sdk.armor");
+    std::fs::write(&armored, &stdout[stdout.find("-----BEGIN").unwrap()..]).unwrap();
+
+    let args = ["import", "copy", armored.to_str().unwrap()];
+    let out = keys_with_input(dir.path(), &args, "passphrase\n");
+    assert!(out.status.success());
+    assert_eq!(
+        keyring.get("copy").unwrap().pub_key,
+        keyring.get("sdk").unwrap().pub_key
+    );
+}
+
+/// `keys import-hex` stores raw secp256k1 and ed25519 keys
+#[test]
+fn import_hex_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let secp256k1 = "0xc4a48e2fce1481cd3294b4490f6678090ea98d3d0e5cd984558ab0968741b104";
+    let ed25519 = "0202020202020202020202020202020202020202020202020202020202020202";
+
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner.args(["import-hex", "alice", secp256k1]).run();
+    cmd.wait().unwrap().expect_success();
+
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner
+        .args(["import-hex", "bob", ed25519, "--key-type", "ed25519"])
+        .run();
+    cmd.wait().unwrap().expect_success();
+
+    let keyring = FileKeyring::new_test(dir.path().join("keyring-test"));
+    assert_eq!(
+        hex::encode(keyring.get("alice").unwrap().address()),
+        "28ff5c6d57d8cfd492b6fb42614536ed648e01fd"
+    );
+    assert_eq!(
+        keyring.get("bob").unwrap().pub_key.type_url(),
+        "/cosmos.crypto.ed25519.PubKey"
+    );
+}
+
+/// `keys mnemonic --unsafe-entropy` hashes the user input into the entropy
+#[test]
+fn mnemonic_from_user_entropy() {
+    let dir = tempfile::tempdir().unwrap();
+    let entropy = "hhhhtthhhthtththhhtttthhhttttththhthhtthtthththhth\n";
+
+    let args = ["mnemonic", "--unsafe-entropy"];
+    let first = keys_with_input(dir.path(), &args, &format!("{}y\n", entropy));
+    let second = keys_with_input(dir.path(), &args, &format!("{}y\n", entropy));
+    assert!(first.status.success());
+    let mnemonic = String::from_utf8(first.stdout).unwrap();
+    let mnemonic = mnemonic.lines().last().unwrap().to_owned();
+    assert_eq!(mnemonic.split(' ').count(), 24);
+    assert!(String::from_utf8(second.stdout)
+        .unwrap()
+        .ends_with(&format!("{}\n", mnemonic)));
+
+    let declined = keys_with_input(dir.path(), &args, &format!("{}n\n", entropy));
+    assert!(declined.status.success());
+    assert!(!String::from_utf8(declined.stdout)
+        .unwrap()
+        .contains(&mnemonic));
+
+    let short = keys_with_input(dir.path(), &args, "too short\n");
+    assert!(!short.status.success());
+}
+
+/// `keys parse` converts between hex and bech32 addresses
+#[test]
+fn parse_address() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner
+        .args([
+            "parse",
+            "28ff5c6d57d8cfd492b6fb42614536ed648e01fd",
+            "--output",
+            "json",
+        ])
+        .capture_stdout()
+        .run();
+    let out = read_json(&mut cmd);
+    cmd.wait().unwrap().expect_success();
+    assert!(out.get("human").is_none());
+    assert_eq!(out["bytes"], "28FF5C6D57D8CFD492B6FB42614536ED648E01FD");
+    assert_eq!(
+        out["formats"][0],
+        "cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal4"
+    );
+    assert!(out["formats"][1]
+        .as_str()
+        .unwrap()
+        .starts_with("cosmosvaloper1"));
+    assert!(out["formats"][2]
+        .as_str()
+        .unwrap()
+        .starts_with("cosmosvalcons1"));
+
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner
+        .args([
+            "parse",
+            "osmo19rl4cm2hmr8afy4kldpxz3fka4jguq0a5m7df8",
+            "--bech32-prefix",
+            "osmo",
+            "--output",
+            "json",
+        ])
+        .capture_stdout()
+        .run();
+    let out = read_json(&mut cmd);
+    cmd.wait().unwrap().expect_success();
+    assert_eq!(out["human"], "osmo");
+    assert_eq!(out["bytes"], "28FF5C6D57D8CFD492B6FB42614536ED648E01FD");
+
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner.args(["parse", "not an address"]).run();
+    cmd.wait().unwrap().expect_code(1);
+}
+
+/// `keys migrate` rewrites legacy records found in the keyring
+#[test]
+fn migrate_legacy_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let keyring = FileKeyring::new_test(dir.path().join("keyring-test"));
+    for name in ["local", "offline"] {
+        let fixture = std::fs::read(format!("tests/fixtures/legacy/{}.info", name)).unwrap();
+        keyring
+            .set_item(&format!("{}.info", name), &fixture)
+            .unwrap();
+    }
+
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner.args(["migrate"]).run();
+    cmd.wait().unwrap().expect_success();
+
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner
+        .args(["list", "--output", "json"])
+        .capture_stdout()
+        .run();
+    let out = read_json(&mut cmd);
+    cmd.wait().unwrap().expect_success();
+    assert_eq!(out[0]["name"], "local");
+    assert_eq!(
+        out[0]["address"],
+        "cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal4"
+    );
+    assert_eq!(out[1]["type"], "offline");
+}
+
+/// `keys add --multisig` stores a multisig key over address-sorted keys
+#[test]
+fn add_multisig() {
+    let dir = tempfile::tempdir().unwrap();
+    let keys = [
+        (
+            "alice",
+            "c4a48e2fce1481cd3294b4490f6678090ea98d3d0e5cd984558ab0968741b104",
+        ),
+        (
+            "bob",
+            "0101010101010101010101010101010101010101010101010101010101010101",
+        ),
+    ];
+    for (name, hex) in keys {
+        let mut runner = keys_runner(dir.path());
+        let cmd = runner.args(["import-hex", name, hex]).run();
+        cmd.wait().unwrap().expect_success();
+    }
+
+    for threshold in ["0", "-1", "3"] {
+        let mut runner = keys_runner(dir.path());
+        let cmd = runner
+            .args([
+                "add",
+                "multi",
+                "--multisig",
+                "bob,alice",
+                "--multisig-threshold",
+                threshold,
+            ])
+            .run();
+        cmd.wait()
+            .unwrap()
+            .expect_code(if threshold == "-1" { 2 } else { 1 });
+    }
+
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner
+        .args([
+            "add",
+            "multi",
+            "--multisig",
+            "bob,alice",
+            "--multisig-threshold",
+            "2",
+            "--output",
+            "json",
+        ])
+        .capture_stdout()
+        .run();
+    let out = read_json(&mut cmd);
+    cmd.wait().unwrap().expect_success();
+    assert_eq!(out["type"], "multi");
+    assert!(out.get("mnemonic").is_none());
+
+    let keyring = FileKeyring::new_test(dir.path().join("keyring-test"));
+    assert_eq!(
+        hex::encode(keyring.get("multi").unwrap().address()),
+        "7b81859bb88b3933797698773827c24ec2b05d54"
+    );
+
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner
+        .args([
+            "add",
+            "unsorted",
+            "--multisig",
+            "bob,alice",
+            "--multisig-threshold",
+            "2",
+            "--nosort",
+        ])
+        .run();
+    cmd.wait().unwrap().expect_success();
+    assert_eq!(
+        hex::encode(keyring.get("unsorted").unwrap().address()),
+        "abad3b77477fb635b180ab6e877e513fdd0056c2"
+    );
+
+    // members are resolved before the key is overridden
+    let args = ["add", "multi", "--multisig", "bob,carol"];
+    let out = keys_with_input(dir.path(), &args, "y\n");
+    assert!(!out.status.success());
+    assert_eq!(
+        hex::encode(keyring.get("multi").unwrap().address()),
+        "7b81859bb88b3933797698773827c24ec2b05d54"
+    );
+
+    let args = [
+        "add",
+        "bob",
+        "--multisig",
+        "bob,alice",
+        "--multisig-threshold",
+        "2",
+    ];
+    let out = keys_with_input(dir.path(), &args, "y\n");
+    assert!(out.status.success());
+    assert_eq!(
+        keyring.get("bob").unwrap().address(),
+        keyring.get("multi").unwrap().address()
+    );
+}
+
+/// `keys add --pubkey` stores an offline record for a JSON-encoded key
+#[test]
+fn add_offline() {
+    let dir = tempfile::tempdir().unwrap();
+    let pubkey = r#"{"@type":"/cosmos.crypto.secp256k1.PubKey","key":"Ak9OKtmcNNYLm6YoPJQxqEGK+GcyEpYfl6d7Y3f80Fti"}"#;
+
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner
+        .args(["add", "watch", "--pubkey", pubkey, "--output", "json"])
+        .capture_stdout()
+        .run();
+    let out = read_json(&mut cmd);
+    cmd.wait().unwrap().expect_success();
+    assert_eq!(out["type"], "offline");
+    assert_eq!(out["pubkey"], pubkey);
+    assert!(out.get("mnemonic").is_none());
+
+    let keyring = FileKeyring::new_test(dir.path().join("keyring-test"));
+    let record = keyring.get("watch").unwrap();
+    assert!(matches!(record.item, KeyItem::Offline));
+    assert_eq!(
+        record.pub_key,
+        PubKey::from_json(&serde_json::from_str(pubkey).unwrap()).unwrap()
+    );
+
+    for pubkey in [
+        "not json",
+        r#"{"@type":"/cosmos.crypto.secp256k1.PubKey","key":"AAAA"}"#,
+        r#"{"@type":"/cosmos.crypto.sr25519.PubKey","key":"Ak9OKtmcNNYLm6YoPJQxqEGK+GcyEpYfl6d7Y3f80Fti"}"#,
+    ] {
+        let mut runner = keys_runner(dir.path());
+        let cmd = runner.args(["add", "bad", "--pubkey", pubkey]).run();
+        cmd.wait().unwrap().expect_code(1);
+    }
+}
+
+/// `keys add --algo` derives and records keys of the requested algorithm
+#[test]
+fn add_with_algo() {
+    let dir = tempfile::tempdir().unwrap();
+    for algo in ["ed25519", "eth_secp256k1"] {
+        let mut runner = keys_runner(dir.path());
+        let cmd = runner
+            .args(["add", algo, "--algo", algo, "--no-backup"])
+            .run();
+        cmd.wait().unwrap().expect_success();
+    }
+
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner.args(["add", "sr", "--algo", "sr25519"]).run();
+    cmd.wait().unwrap().expect_code(2);
+
+    let keyring = FileKeyring::new_test(dir.path().join("keyring-test"));
+    assert_eq!(
+        keyring.get("ed25519").unwrap().algo(),
+        Some(SigningAlgo::Ed25519)
+    );
+    assert_eq!(
+        keyring.get("eth_secp256k1").unwrap().algo(),
+        Some(SigningAlgo::EthSecp256k1)
+    );
+    assert!(!keyring.exists("sr").unwrap());
+
+    // eth_secp256k1 keys are derived with coin type 60 like Ethermint
+    // wallets do, unless told otherwise
+    let phrase = "abandon abandon abandon abandon abandon abandon \
+                  abandon abandon abandon abandon abandon about\n";
+    for args in [
+        &["add", "eth", "--algo", "eth_secp256k1", "--recover"][..],
+        &[
+            "add",
+            "eth118",
+            "--algo",
+            "eth_secp256k1",
+            "--coin-type",
+            "118",
+            "--recover",
+        ],
+    ] {
+        let out = keys_with_input(dir.path(), args, phrase);
+        assert!(out.status.success(), "{:?}", out);
+    }
+    assert_eq!(
+        hex::encode(keyring.get("eth").unwrap().address()),
+        "9858effd232b4033e47d90003d41ec34ecaeda94"
+    );
+    assert_ne!(
+        keyring.get("eth118").unwrap().address(),
+        keyring.get("eth").unwrap().address()
+    );
+}
+
+/// `keys add --recover` and `--interactive` read the mnemonic and BIP39
+/// passphrase from stdin and never print a supplied mnemonic
+#[test]
+fn add_recover_and_interactive() {
+    let dir = tempfile::tempdir().unwrap();
+    let phrase = "abandon abandon abandon abandon abandon abandon \
+                  abandon abandon abandon abandon abandon about";
+    let keyring = FileKeyring::new_test(dir.path().join("keyring-test"));
+    let local_key = |name: &str| match keyring.get(name).unwrap().item {
+        KeyItem::Local(priv_key) => hex::encode(priv_key.to_bytes()),
+        _ => panic!("{} is not a local key", name),
+    };
+
+    let out = keys_with_input(
+        dir.path(),
+        &["add", "recovered", "--recover"],
+        &format!("{}\n", phrase),
+    );
+    assert!(out.status.success());
+    assert!(!String::from_utf8_lossy(&out.stdout).contains("abandon"));
+    assert_eq!(
+        local_key("recovered"),
+        "c4a48e2fce1481cd3294b4490f6678090ea98d3d0e5cd984558ab0968741b104"
+    );
+
+    let invalid = phrase.replace("about", "abandon");
+    let out = keys_with_input(
+        dir.path(),
+        &["add", "invalid", "--recover"],
+        &format!("{}\n", invalid),
+    );
+    assert!(!out.status.success());
+    assert!(!keyring.exists("invalid").unwrap());
+
+    let out = keys_with_input(
+        dir.path(),
+        &["add", "salted", "--interactive"],
+        &format!("{}\nsalt\nsalt\n", phrase),
+    );
+    assert!(out.status.success());
+    assert!(!String::from_utf8_lossy(&out.stdout).contains("abandon"));
+    let mnemonic = hd::parse_mnemonic(phrase).unwrap();
+    let expected = hd::derive(
+        SigningAlgo::Secp256k1,
+        &mnemonic,
+        "salt",
+        &"m/44'/118'/0'/0/0".parse().unwrap(),
+    );
+    assert_eq!(
+        local_key("salted"),
+        hex::encode(expected.unwrap().to_bytes())
+    );
+
+    let out = keys_with_input(
+        dir.path(),
+        &["add", "mismatch", "--interactive"],
+        &format!("{}\nsalt\npepper\n", phrase),
+    );
+    assert!(!out.status.success());
+    assert!(!keyring.exists("mismatch").unwrap());
+
+    // a typo in the mnemonic or passphrase does not destroy the key it
+    // would override
+    for (args, input) in [
+        (
+            &["add", "recovered", "--recover"][..],
+            format!("{}\ny\n", invalid),
+        ),
+        (
+            &["add", "recovered", "--interactive"],
+            format!("{}\nsalt\npepper\ny\n", phrase),
+        ),
+    ] {
+        let out = keys_with_input(dir.path(), args, &input);
+        assert!(!out.status.success());
+        assert_eq!(
+            local_key("recovered"),
+            "c4a48e2fce1481cd3294b4490f6678090ea98d3d0e5cd984558ab0968741b104"
+        );
+    }
+
+    // an empty mnemonic generates a fresh one, which is shown
+    let out = keys_with_input(dir.path(), &["add", "fresh", "--interactive"], "\n\n");
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("**Important**"));
+}
+
+/// `keys add` validates HD paths and `keys show` renders ledger paths
+#[test]
+fn hd_paths() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut runner = keys_runner(dir.path());
+    let cmd = runner
+        .args([
+            "add",
+            "hardened",
+            "--hd-path",
+            "m/44h/118h/0h/0/7",
+            "--no-backup",
+        ])
+        .run();
+    cmd.wait().unwrap().expect_success();
+
+    for args in [
+        &["add", "bad", "--hd-path", "m/44'/118'/0'/0/2147483648"][..],
+        &["add", "bad", "--hd-path", "44'/118'/0'/0/0"],
+    ] {
+        let mut runner = keys_runner(dir.path());
+        let cmd = runner.args(args).run();
+        cmd.wait().unwrap().expect_code(2);
+    }
+    for args in [
+        &["add", "bad", "--account", "2147483648"][..],
+        &[
+            "add",
+            "bad",
+            "--algo",
+            "ed25519",
+            "--hd-path",
+            "m/44'/118'/0'/0/0",
+        ],
+    ] {
+        let mut runner = keys_runner(dir.path());
+        let cmd = runner.args(args).run();
+        cmd.wait().unwrap().expect_code(1);
+    }
+
+    let keyring = FileKeyring::new_test(dir.path().join("keyring-test"));
+    assert!(!keyring.exists("bad").unwrap());
+    let pub_key = keyring.get("hardened").unwrap().pub_key;
+
+    // an invalid path does not destroy the key it would override
+    for index in ["--account", "--index"] {
+        let args = ["add", "hardened", index, "2147483648"];
+        let out = keys_with_input(dir.path(), &args, "y\n");
+        assert!(!out.status.success());
+        assert_eq!(keyring.get("hardened").unwrap().pub_key, pub_key);
+    }
+    let params = "m/44'/118'/0'/0/7"
+        .parse::<HdPath>()
+        .unwrap()
+        .bip44_params();
+    keyring
+        .put(&KeyRecord::new_ledger("nano", pub_key, params.unwrap()))
+        .unwrap();
+
+    let mut runner = keys_runner(dir.path());
+    let mut cmd = runner
+        .args(["show", "nano", "--output", "json"])
+        .capture_stdout()
+        .run();
+    let out = read_json(&mut cmd);
+    cmd.wait().unwrap().expect_success();
+    assert_eq!(out["type"], "ledger");
+    assert_eq!(out["path"], "m/44'/118'/0'/0/7");
+}
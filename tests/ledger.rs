@@ -0,0 +1,249 @@
+//! Ledger tests, run against a minimal stand-in for the Speculos APDU
+//! server answering like the Cosmos app.
+
+#![forbid(unsafe_code)]
+#![warn(
+    missing_docs,
+    rust_2018_idioms,
+    trivial_casts,
+    unused_lifetimes,
+    unused_qualifications
+)]
+
+use amon::address;
+use amon::crypto::PubKey;
+use amon::hd::HdPath;
+use amon::keyring::{FileKeyring, KeyItem, Keyring};
+use amon::ledger::{self, ApduAnswer, ApduCommand, CosmosApp, SpeculosTransport};
+use k256::ecdsa::{
+    signature::{Signer, Verifier},
+    Signature, SigningKey,
+};
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener},
+    path::Path,
+    process::{Command, Output, Stdio},
+    thread::{self, JoinHandle},
+};
+
+/// Key held by the emulated device, whatever the path
+fn device_key() -> SigningKey {
+    SigningKey::from_slice(&[7; 32]).unwrap()
+}
+
+/// Serve a single connection like the Cosmos app would, returning the APDUs
+/// received once the client disconnects
+fn spawn_speculos() -> (SocketAddr, JoinHandle<Vec<Vec<u8>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let key = device_key();
+        let mut apdus = Vec::new();
+        let mut message = Vec::new();
+
+        loop {
+            let mut len = [0u8; 4];
+            if stream.read_exact(&mut len).is_err() {
+                return apdus;
+            }
+            let mut apdu = vec![0u8; u32::from_be_bytes(len) as usize];
+            stream.read_exact(&mut apdu).unwrap();
+            assert_eq!(apdu[0], ledger::CLA);
+            assert_eq!(apdu[4] as usize, apdu.len() - 5);
+            let data = &apdu[5..];
+
+            let (answer, status): (Vec<u8>, u16) = match (apdu[1], apdu[2]) {
+                (ledger::INS_GET_VERSION, _) => (vec![0, 2, 34, 12, 0], 0x9000),
+                (ledger::INS_GET_ADDR_SECP256K1, _) => {
+                    let hrp = std::str::from_utf8(&data[1..=data[0] as usize]).unwrap();
+                    let pub_key = PubKey::Secp256k1(*key.verifying_key());
+                    let addr = address::encode(hrp, &pub_key.address()).unwrap();
+                    ([pub_key.to_bytes(), addr.into_bytes()].concat(), 0x9000)
+                }
+                (ledger::INS_SIGN_SECP256K1, 0) => {
+                    message.clear();
+                    (Vec::new(), 0x9000)
+                }
+                (ledger::INS_SIGN_SECP256K1, 1) => {
+                    message.extend_from_slice(data);
+                    (Vec::new(), 0x9000)
+                }
+                (ledger::INS_SIGN_SECP256K1, 2) => {
+                    message.extend_from_slice(data);
+                    let signature: Signature = key.sign(&message);
+                    (signature.to_der().as_bytes().to_vec(), 0x9000)
+                }
+                _ => (Vec::new(), 0x6d00),
+            };
+
+            let mut frame = (answer.len() as u32).to_be_bytes().to_vec();
+            frame.extend_from_slice(&answer);
+            frame.extend_from_slice(&status.to_be_bytes());
+            stream.write_all(&frame).unwrap();
+            apdus.push(apdu);
+        }
+    });
+
+    (addr, handle)
+}
+
+/// Run `keys` subcommands with the test backend in `dir`, using the
+/// emulator at `speculos` and agreeing to override existing keys
+fn keys(dir: &Path, speculos: &str, args: &[&str]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_amon"))
+        .args(["--keyring-backend", "test", "--keyring-dir"])
+        .arg(dir)
+        .arg("keys")
+        .args(args)
+        .env(ledger::SPECULOS_ADDR_ENV, speculos)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child.stdin.take().unwrap().write_all(b"y\n").unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// APDUs are framed as `CLA INS P1 P2 Lc data` and answered with a status word
+#[test]
+fn apdu_framing() {
+    let command = ApduCommand {
+        cla: 0x55,
+        ins: 0x04,
+        p1: 1,
+        p2: 0,
+        data: vec![0xaa, 0xbb],
+    };
+    assert_eq!(command.encode().unwrap(), [0x55, 0x04, 1, 0, 2, 0xaa, 0xbb]);
+
+    let too_long = ApduCommand {
+        data: vec![0; 256],
+        ..command
+    };
+    assert!(too_long.encode().is_err());
+
+    let answer = ApduAnswer::from_bytes(&[1, 2, 0x90, 0x00]).unwrap();
+    assert_eq!(answer.status, ledger::SW_OK);
+    assert_eq!(answer.into_result().unwrap(), [1, 2]);
+
+    let rejected = ApduAnswer::from_bytes(&[0x69, 0x86]).unwrap();
+    let err = rejected.into_result().unwrap_err();
+    assert!(err.to_string().contains("transaction rejected"));
+
+    assert!(ApduAnswer::from_bytes(&[0x90]).is_err());
+}
+
+/// The Cosmos app client gets the version, public key and signatures
+#[test]
+fn cosmos_app() {
+    let (addr, handle) = spawn_speculos();
+    let mut app = CosmosApp::new(SpeculosTransport::connect(addr).unwrap()).unwrap();
+
+    let version = app.version().unwrap();
+    assert_eq!(version.to_string(), "2.34.12");
+    assert!(!version.test_mode);
+
+    let path = "m/44'/118'/1'/0/2"
+        .parse::<HdPath>()
+        .unwrap()
+        .bip44_params()
+        .unwrap();
+    let (pub_key, address) = app.get_address_pub_key(&path, "cosmos", false).unwrap();
+    assert_eq!(pub_key, PubKey::Secp256k1(*device_key().verifying_key()));
+    assert!(address.starts_with("cosmos1"));
+
+    let message = vec![b'x'; 600];
+    let signature = app.sign_secp256k1(&path, &message).unwrap();
+    device_key()
+        .verifying_key()
+        .verify(&message, &signature)
+        .unwrap();
+    assert!(signature.normalize_s().is_none());
+    drop(app);
+
+    let apdus = handle.join().unwrap();
+    // version twice, address, then the path and three chunks to sign
+    assert_eq!(apdus.len(), 7);
+    let path_bytes = [44 | 1 << 31, 118 | 1 << 31, 1 | 1 << 31, 0u32, 2]
+        .iter()
+        .flat_map(|c| c.to_le_bytes())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        apdus[2][5..],
+        [&[6][..], b"cosmos", &path_bytes].concat()[..]
+    );
+    assert_eq!(apdus[3][5..], path_bytes[..]);
+    assert_eq!(
+        apdus[4..].iter().map(|apdu| apdu[2]).collect::<Vec<_>>(),
+        [1, 1, 2]
+    );
+}
+
+/// `keys add --ledger` stores a reference to the device key and its path
+#[test]
+fn add_ledger_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let (addr, handle) = spawn_speculos();
+
+    let out = keys(
+        dir.path(),
+        &addr.to_string(),
+        &["add", "nano", "--ledger", "--account", "1", "--index", "2"],
+    );
+    assert!(out.status.success(), "{:?}", out);
+    handle.join().unwrap();
+
+    let keyring = FileKeyring::new_test(dir.path().join("keyring-test"));
+    let record = keyring.get("nano").unwrap();
+    assert_eq!(
+        record.pub_key,
+        PubKey::Secp256k1(*device_key().verifying_key())
+    );
+    match record.item {
+        KeyItem::Ledger(path) => assert_eq!(path.to_string(), "m/44'/118'/1'/0/2"),
+        _ => panic!("not a ledger key"),
+    }
+
+    let out = keys(
+        dir.path(),
+        &addr.to_string(),
+        &[
+            "add",
+            "custom",
+            "--ledger",
+            "--hd-path",
+            "m/44'/118'/0'/0/0",
+        ],
+    );
+    assert!(!out.status.success());
+    assert!(
+        String::from_utf8_lossy(&out.stderr).contains("cannot set custom bip32 path with ledger")
+    );
+
+    // the emulator is gone
+    let out = keys(dir.path(), &addr.to_string(), &["add", "gone", "--ledger"]);
+    assert!(!out.status.success());
+    assert!(!keyring.exists("custom").unwrap());
+    assert!(!keyring.exists("gone").unwrap());
+
+    // failing to reach the device or invalid flags keep the key they would
+    // override
+    let out = keys(dir.path(), &addr.to_string(), &["add", "nano", "--ledger"]);
+    assert!(!out.status.success());
+    for args in [
+        &["add", "nano", "--ledger", "--hd-path", "m/44'/118'/0'/0/0"][..],
+        &["add", "nano", "--ledger", "--algo", "ed25519"],
+    ] {
+        let out = keys(dir.path(), &addr.to_string(), args);
+        assert!(!out.status.success());
+    }
+    assert!(matches!(
+        keyring.get("nano").unwrap().item,
+        KeyItem::Ledger(_)
+    ));
+}